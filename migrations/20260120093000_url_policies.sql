@@ -0,0 +1,15 @@
+-- Per-guild domain allowlist/denylist for /play links
+
+CREATE TABLE IF NOT EXISTS guild_url_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    guild_id INTEGER NOT NULL,
+    domain TEXT NOT NULL,
+    rule TEXT NOT NULL CHECK(rule IN ('allow', 'deny')),
+    created_by INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(guild_id, domain)
+);
+
+CREATE INDEX idx_guild_url_rules_guild ON guild_url_rules(guild_id);
+
+ALTER TABLE guild_configs ADD COLUMN allow_http_urls BOOLEAN DEFAULT 1;
@@ -0,0 +1,4 @@
+-- How track announcements are posted, and whether old ones are cleaned up
+
+ALTER TABLE guild_configs ADD COLUMN announce_mode TEXT NOT NULL DEFAULT 'edit' CHECK(announce_mode IN ('off', 'compact', 'full', 'edit'));
+ALTER TABLE guild_configs ADD COLUMN announce_delete_previous BOOLEAN DEFAULT 0;
@@ -0,0 +1,3 @@
+-- Optionally give each listening session its own announcement thread
+
+ALTER TABLE guild_configs ADD COLUMN announce_thread BOOLEAN DEFAULT 0;
@@ -0,0 +1,4 @@
+-- Optionally pause when the voice channel empties, and leave after a separate timeout
+
+ALTER TABLE guild_configs ADD COLUMN pause_when_alone BOOLEAN DEFAULT 0;
+ALTER TABLE guild_configs ADD COLUMN alone_timeout INTEGER DEFAULT 600;
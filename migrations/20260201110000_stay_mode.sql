@@ -0,0 +1,4 @@
+-- 24/7 mode: a voice channel the bot stays in, and a guild playlist to fall back on
+
+ALTER TABLE guild_configs ADD COLUMN stay_channel_id INTEGER;
+ALTER TABLE guild_configs ADD COLUMN fallback_playlist_id INTEGER;
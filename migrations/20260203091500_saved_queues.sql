@@ -0,0 +1,9 @@
+-- Keep the queue when the bot is disconnected from voice so it can be restored
+
+CREATE TABLE IF NOT EXISTS saved_queues (
+    guild_id INTEGER PRIMARY KEY,
+    tracks TEXT NOT NULL,
+    saved_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+ALTER TABLE guild_configs ADD COLUMN save_queue_on_disconnect BOOLEAN DEFAULT 1;
@@ -0,0 +1,3 @@
+-- Show the current track as the bot's voice channel status
+
+ALTER TABLE guild_configs ADD COLUMN voice_status BOOLEAN DEFAULT 1;
@@ -0,0 +1,10 @@
+-- Stop playback after a while or at the end of the track or queue
+
+CREATE TABLE IF NOT EXISTS sleep_timers (
+    guild_id INTEGER PRIMARY KEY,
+    mode TEXT NOT NULL,
+    ends_at INTEGER,
+    disconnect BOOLEAN NOT NULL DEFAULT 0,
+    channel_id INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
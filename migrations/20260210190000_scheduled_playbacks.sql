@@ -0,0 +1,16 @@
+-- Guild playlists that start playing in a voice channel at a set time
+
+CREATE TABLE IF NOT EXISTS scheduled_playbacks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    guild_id INTEGER NOT NULL,
+    playlist_id INTEGER NOT NULL,
+    voice_channel_id INTEGER NOT NULL,
+    starts_at INTEGER NOT NULL,
+    repeat_every TEXT NOT NULL DEFAULT 'none',
+    created_by INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (playlist_id) REFERENCES guild_playlists(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_scheduled_playbacks_guild ON scheduled_playbacks(guild_id);
+CREATE INDEX idx_scheduled_playbacks_starts ON scheduled_playbacks(starts_at);
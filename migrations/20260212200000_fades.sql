@@ -0,0 +1,4 @@
+-- Fade tracks in and out, and fade out at the end of a track before the next one
+
+ALTER TABLE guild_configs ADD COLUMN fade_duration INTEGER DEFAULT 0;
+ALTER TABLE guild_configs ADD COLUMN crossfade BOOLEAN DEFAULT 0;
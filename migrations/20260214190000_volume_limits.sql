@@ -0,0 +1,11 @@
+-- Highest volume anyone can set in a guild, optionally lower for some roles
+
+ALTER TABLE guild_configs ADD COLUMN max_volume INTEGER DEFAULT 200;
+
+CREATE TABLE IF NOT EXISTS role_volume_limits (
+    guild_id INTEGER NOT NULL,
+    role_id INTEGER NOT NULL,
+    max_volume INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (guild_id, role_id)
+);
@@ -2,8 +2,14 @@ use crate::{
     Context, Error,
     database::queries,
     utils::{
-        constants::{COLOR_INFO, COLOR_SUCCESS, COLOR_WARNING},
+        announcements::AnnounceMode,
+        constants::{COLOR_ERROR, COLOR_INFO, COLOR_SUCCESS, COLOR_WARNING},
         emojis::get_emoji,
+        fade,
+        player_data::PlayerData,
+        stay,
+        url_policy::{self, RULE_ALLOW, RULE_DENY},
+        voice_status, volume,
     },
 };
 use ::serenity::all::Mentionable;
@@ -16,10 +22,18 @@ use poise::serenity_prelude as serenity;
         "view",
         "djrole",
         "volume",
+        "maxvolume",
         "autodisconnect",
+        "alone",
+        "stay",
+        "savequeue",
+        "voicestatus",
+        "fade",
         "announce",
+        "announcemode",
         "maxqueue",
         "filters",
+        "urls",
         "reset"
     )
 )]
@@ -50,20 +64,107 @@ pub async fn view(ctx: Context<'_>) -> Result<(), Error> {
         "Disabled".to_string()
     };
 
+    let stay = match config.stay_channel_id {
+        Some(channel_id) => {
+            let fallback = match config.fallback_playlist_id {
+                Some(playlist_id) => queries::get_guild_playlists(db, guild_id)
+                    .await?
+                    .into_iter()
+                    .find(|p| p.id == playlist_id)
+                    .map_or_else(
+                        || "missing playlist".to_string(),
+                        |p| format!("playlist **{}**", p.name),
+                    ),
+                None => "no fallback playlist".to_string(),
+            };
+            format!("<#{channel_id}> ({fallback})")
+        }
+        None => "Disabled".to_string(),
+    };
+
+    let role_limits = queries::get_role_volume_limits(db, guild_id).await?;
+    let max_volume = if role_limits.is_empty() {
+        format!("{}%", config.max_volume)
+    } else {
+        let roles = role_limits
+            .iter()
+            .map(|l| format!("<@&{}> {}%", l.role_id, l.max_volume))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{}% ({roles})", config.max_volume)
+    };
+
     let embed = serenity::CreateEmbed::default()
         .title("Guild Configuration")
         .field("DJ Role", dj_role, false)
         .field("Default Volume", format!("{}%", config.volume), true)
+        .field("Max Volume", max_volume, true)
         .field(
             "Max Queue Length",
             config.max_queue_length.to_string(),
             true,
         )
         .field("Auto Disconnect", auto_disconnect, false)
+        .field(
+            "When Alone",
+            format!(
+                "{}leave after {}s",
+                if config.pause_when_alone {
+                    "Pause, "
+                } else {
+                    ""
+                },
+                config.alone_timeout
+            ),
+            false,
+        )
+        .field("24/7", stay, false)
+        .field(
+            "Fades",
+            if config.fade_duration > 0 {
+                format!(
+                    "{}s{}",
+                    config.fade_duration,
+                    if config.crossfade { ", crossfade" } else { "" }
+                )
+            } else {
+                "Off".to_string()
+            },
+            true,
+        )
+        .field(
+            "Track as Voice Status",
+            config.voice_status.to_string(),
+            true,
+        )
+        .field(
+            "Save Queue on Disconnect",
+            config.save_queue_on_disconnect.to_string(),
+            true,
+        )
         .field("Announce Songs", config.announce_songs.to_string(), true)
         .field("Announce Channel", announce_channel, true)
+        .field(
+            "Announce Mode",
+            format!(
+                "{}{}",
+                config.announce_mode,
+                if config.announce_delete_previous {
+                    " (deletes previous)"
+                } else {
+                    ""
+                }
+            ),
+            true,
+        )
+        .field("Session Threads", config.announce_thread.to_string(), true)
         .field("Allow Filters", config.allow_filters.to_string(), true)
         .field("Allow Explicit", config.allow_explicit.to_string(), true)
+        .field(
+            "Allow http:// Links",
+            config.allow_http_urls.to_string(),
+            true,
+        )
         .color(COLOR_INFO);
 
     ctx.send(poise::CreateReply::default().embed(embed)).await?;
@@ -113,7 +214,17 @@ async fn volume(
     let guild_id = ctx.guild_id().ok_or("Must be in a guild")?.get() as i64;
     let db = ctx.data().database.pool();
 
-    queries::update_volume(db, guild_id, vol).await?;
+    let config = queries::get_guild_config(db, guild_id).await?;
+    let set = vol.min(config.max_volume);
+    queries::update_volume(db, guild_id, set).await?;
+
+    let description = if set < vol {
+        format!(
+            "New tracks will play at {set}%, this server's max volume. Raise it with `/config maxvolume server`"
+        )
+    } else {
+        format!("New tracks will play at {set}%")
+    };
 
     let volume_emoji = get_emoji(ctx.serenity_context(), "vol3").await;
     let embed = serenity::CreateEmbed::default()
@@ -121,13 +232,129 @@ async fn volume(
             "{} Default Volume Updated",
             volume_emoji.unwrap_or_default()
         ))
-        .description(format!("New tracks will play at {vol}%"))
+        .description(description)
         .color(COLOR_SUCCESS);
 
     ctx.send(poise::CreateReply::default().embed(embed)).await?;
     Ok(())
 }
 
+/// Limit how loud the music can be turned up
+#[poise::command(
+    slash_command,
+    subcommands("maxvolume_server", "maxvolume_role", "maxvolume_remove")
+)]
+async fn maxvolume(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Set the highest volume anyone can set on this server
+#[poise::command(slash_command, rename = "server")]
+async fn maxvolume_server(
+    ctx: Context<'_>,
+    #[description = "Max volume (1-200)"]
+    #[min = 1]
+    #[max = 200]
+    max: i16,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?;
+    let db = ctx.data().database.pool();
+
+    queries::update_max_volume(db, guild_id.get() as i64, max).await?;
+
+    let mut description = format!("Nobody can set the volume above {max}%");
+    if let Some(player) = ctx.data().lavalink.get_player_context(guild_id)
+        && let Some(lowered) = volume::enforce(&player, max as u16).await?
+    {
+        description.push_str(&format!("\nThe music was turned down to {lowered}%"));
+    }
+
+    let volume_emoji = get_emoji(ctx.serenity_context(), "vol3").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Max Volume Updated",
+            volume_emoji.unwrap_or_default()
+        ))
+        .description(description)
+        .color(COLOR_SUCCESS);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Set a lower max volume for members with a role
+#[poise::command(slash_command, rename = "role")]
+async fn maxvolume_role(
+    ctx: Context<'_>,
+    #[description = "Role to limit"] role: serenity::Role,
+    #[description = "Max volume for this role (1-200)"]
+    #[min = 1]
+    #[max = 200]
+    max: i16,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?.get() as i64;
+    let db = ctx.data().database.pool();
+
+    queries::set_role_volume_limit(db, guild_id, role.id.get() as i64, max).await?;
+    let config = queries::get_guild_config(db, guild_id).await?;
+
+    let mut description = format!(
+        "Members with {} can't set the volume above {max}%",
+        role.mention()
+    );
+    if max >= config.max_volume {
+        description.push_str(&format!(
+            "\nThis server's max volume of {}% is lower, so it still applies",
+            config.max_volume
+        ));
+    }
+
+    let volume_emoji = get_emoji(ctx.serenity_context(), "vol3").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Max Volume Updated",
+            volume_emoji.unwrap_or_default()
+        ))
+        .description(description)
+        .color(COLOR_SUCCESS);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Remove the max volume of a role
+#[poise::command(slash_command, rename = "remove")]
+async fn maxvolume_remove(
+    ctx: Context<'_>,
+    #[description = "Role to remove the limit of"] role: serenity::Role,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?.get() as i64;
+    let db = ctx.data().database.pool();
+
+    let embed = if queries::remove_role_volume_limit(db, guild_id, role.id.get() as i64).await? {
+        let success_emoji = get_emoji(ctx.serenity_context(), "check").await;
+        serenity::CreateEmbed::default()
+            .title(format!(
+                "{} Max Volume Updated",
+                success_emoji.unwrap_or_default()
+            ))
+            .description(format!(
+                "{} now has the server's max volume",
+                role.mention()
+            ))
+            .color(COLOR_SUCCESS)
+    } else {
+        let error_emoji = get_emoji(ctx.serenity_context(), "cross").await;
+        serenity::CreateEmbed::default()
+            .title(format!("{} No Role Limit", error_emoji.unwrap_or_default()))
+            .description(format!("{} has no max volume of its own", role.mention()))
+            .color(COLOR_ERROR)
+    };
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
 /// Configure auto disconnect when inactive
 #[poise::command(slash_command)]
 async fn autodisconnect(
@@ -161,6 +388,241 @@ async fn autodisconnect(
     Ok(())
 }
 
+/// Configure what happens when everyone leaves the voice channel
+#[poise::command(slash_command)]
+async fn alone(
+    ctx: Context<'_>,
+    #[description = "Pause when everyone leaves and resume when someone comes back"] pause: bool,
+    #[description = "Seconds to stay alone before disconnecting (default: 600)"]
+    #[min = 1]
+    timeout: Option<i32>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?.get() as i64;
+    let db = ctx.data().database.pool();
+
+    let config = queries::get_guild_config(db, guild_id).await?;
+    let timeout = timeout.unwrap_or(config.alone_timeout);
+    queries::update_alone_settings(db, guild_id, pause, timeout).await?;
+
+    let mut description = if pause {
+        "Playback will pause when everyone leaves and resume when someone returns".to_string()
+    } else {
+        "Playback will continue when everyone leaves".to_string()
+    };
+
+    if config.auto_disconnect {
+        description.push_str(&format!(
+            "\nBot will disconnect after {timeout} seconds alone"
+        ));
+    } else {
+        description
+            .push_str("\nAuto disconnect is disabled, turn it on with `/config autodisconnect`");
+    }
+
+    let clock_emoji = get_emoji(ctx.serenity_context(), "clock").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Alone Behaviour Updated",
+            clock_emoji.unwrap_or_default()
+        ))
+        .description(description)
+        .color(COLOR_SUCCESS);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Keep the bot in a voice channel around the clock
+#[poise::command(slash_command, rename = "247")]
+async fn stay(
+    ctx: Context<'_>,
+    #[description = "Voice channel to stay in (leave empty to turn 24/7 off)"]
+    #[channel_types("Voice", "Stage")]
+    channel: Option<serenity::GuildChannel>,
+    #[description = "Guild playlist to play whenever the queue is empty"] playlist: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?;
+    let db = ctx.data().database.pool();
+    let error_emoji = get_emoji(ctx.serenity_context(), "cross").await;
+
+    let playlist = match playlist {
+        Some(name) => {
+            let found = queries::get_guild_playlists(db, guild_id.get() as i64)
+                .await?
+                .into_iter()
+                .find(|p| p.name.eq_ignore_ascii_case(&name));
+
+            let Some(found) = found else {
+                let embed = serenity::CreateEmbed::default()
+                    .title(format!(
+                        "{} Playlist Not Found",
+                        error_emoji.unwrap_or_default()
+                    ))
+                    .description(format!("This server has no playlist named **{name}**."))
+                    .color(COLOR_ERROR);
+
+                ctx.send(poise::CreateReply::default().embed(embed)).await?;
+                return Ok(());
+            };
+            Some(found)
+        }
+        None => None,
+    };
+
+    queries::update_stay_settings(
+        db,
+        guild_id.get() as i64,
+        channel.as_ref().map(|c| c.id.get() as i64),
+        playlist.as_ref().map(|p| p.id),
+    )
+    .await?;
+
+    let description = match (&channel, &playlist) {
+        (Some(channel), Some(playlist)) => format!(
+            "Bot will stay in {} and play **{}** when the queue is empty",
+            channel.mention(),
+            playlist.name
+        ),
+        (Some(channel), None) => format!("Bot will stay in {}", channel.mention()),
+        (None, _) => "24/7 mode disabled".to_string(),
+    };
+
+    if channel.is_some() {
+        if let Err(e) = stay::rejoin(ctx.serenity_context(), ctx.data(), guild_id).await {
+            error!("Failed to join 24/7 channel in guild {}: {:?}", guild_id, e);
+        }
+    } else {
+        ctx.data().auto_disconnect.notify(guild_id);
+    }
+
+    let clock_emoji = get_emoji(ctx.serenity_context(), "clock").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} 24/7 Mode Updated",
+            clock_emoji.unwrap_or_default()
+        ))
+        .description(description)
+        .color(COLOR_SUCCESS);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Save the queue when someone disconnects the bot, so it can be brought back
+#[poise::command(slash_command)]
+async fn savequeue(
+    ctx: Context<'_>,
+    #[description = "Save the queue when the bot is disconnected"] enabled: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?.get() as i64;
+    let db = ctx.data().database.pool();
+
+    queries::update_save_queue_setting(db, guild_id, enabled).await?;
+
+    let description = if enabled {
+        "The queue will be saved when the bot is disconnected, restore it with `/restore`"
+    } else {
+        "The queue will be discarded when the bot is disconnected"
+    };
+
+    let check_emoji = get_emoji(ctx.serenity_context(), "check").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Save Queue Updated",
+            check_emoji.unwrap_or_default()
+        ))
+        .description(description)
+        .color(COLOR_SUCCESS);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Fade tracks in and out instead of cutting them off
+#[poise::command(slash_command)]
+async fn fade(
+    ctx: Context<'_>,
+    #[description = "Fade length in seconds (0 turns fades off)"]
+    #[min = 0]
+    #[max = 10]
+    duration: i32,
+    #[description = "Also fade out at the end of each track before the next one"] crossfade: Option<
+        bool,
+    >,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?;
+    let db = ctx.data().database.pool();
+
+    let config = queries::get_guild_config(db, guild_id.get() as i64).await?;
+    let crossfade = crossfade.unwrap_or(config.crossfade);
+    queries::update_fade_settings(db, guild_id.get() as i64, duration, crossfade).await?;
+
+    if let Some(player) = ctx.data().lavalink.get_player_context(guild_id)
+        && let Ok(data) = player.data::<PlayerData>()
+    {
+        let config = queries::get_guild_config(db, guild_id.get() as i64).await?;
+        fade::configure(&data, &config);
+    }
+
+    let description = match (duration, crossfade) {
+        (0, _) => "Tracks will start and stop without fading".to_string(),
+        (duration, true) => format!(
+            "Tracks will fade in and out over {duration} seconds, including at the end of each track"
+        ),
+        (duration, false) => format!(
+            "Tracks will fade in, and fade out over {duration} seconds when skipped or stopped"
+        ),
+    };
+
+    let check_emoji = get_emoji(ctx.serenity_context(), "check").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!("{} Fades Updated", check_emoji.unwrap_or_default()))
+        .description(description)
+        .color(COLOR_SUCCESS);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Show the current track as the bot's voice channel status
+#[poise::command(slash_command)]
+async fn voicestatus(
+    ctx: Context<'_>,
+    #[description = "Set the voice channel status to the current track"] enabled: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?;
+    let db = ctx.data().database.pool();
+
+    queries::update_voice_status_setting(db, guild_id.get() as i64, enabled).await?;
+
+    if let Some(player) = ctx.data().lavalink.get_player_context(guild_id)
+        && let Ok(data) = player.data::<PlayerData>()
+    {
+        match player.get_player().await?.track {
+            Some(track) if enabled => voice_status::set(&data, voice_status::status(&track)),
+            _ => voice_status::clear(&data),
+        }
+    }
+
+    let description = if enabled {
+        "The voice channel status will show the current track"
+    } else {
+        "The voice channel status will be left alone"
+    };
+
+    let check_emoji = get_emoji(ctx.serenity_context(), "check").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Voice Status Updated",
+            check_emoji.unwrap_or_default()
+        ))
+        .description(description)
+        .color(COLOR_SUCCESS);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
 /// Configure song announcements
 #[poise::command(slash_command)]
 async fn announce(
@@ -169,6 +631,7 @@ async fn announce(
     #[description = "Channel for announcements (leave empty for current channel)"] channel: Option<
         serenity::GuildChannel,
     >,
+    #[description = "Post each listening session in its own thread"] thread: Option<bool>,
 ) -> Result<(), Error> {
     let guild_id = ctx.guild_id().ok_or("Must be in a guild")?.get() as i64;
     let db = ctx.data().database.pool();
@@ -176,7 +639,11 @@ async fn announce(
     let channel_id = channel.as_ref().map(|c| c.id.get() as i64);
     queries::update_announce_settings(db, guild_id, enabled, channel_id).await?;
 
-    let description = if enabled {
+    if let Some(thread) = thread {
+        queries::update_announce_thread(db, guild_id, thread).await?;
+    }
+
+    let mut description = if enabled {
         if let Some(ch) = channel {
             format!("Songs will be announced in {}", ch.mention())
         } else {
@@ -186,6 +653,64 @@ async fn announce(
         "Song announcements disabled".to_string()
     };
 
+    match thread {
+        Some(true) if enabled => {
+            description.push_str("\nEach listening session will get its own thread");
+        }
+        Some(false) => description.push_str("\nSession threads disabled"),
+        _ => {}
+    }
+
+    let song_emoji = get_emoji(ctx.serenity_context(), "song").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Announcements Updated",
+            song_emoji.unwrap_or_default()
+        ))
+        .description(description)
+        .color(COLOR_SUCCESS);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Choose how track announcements are posted
+#[poise::command(slash_command)]
+async fn announcemode(
+    ctx: Context<'_>,
+    #[description = "Off, a one-line compact post, a full post per track, or one message edited in place"]
+    mode: AnnounceMode,
+    #[description = "Delete the previous announcement when the next track starts"]
+    delete_previous: Option<bool>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?.get() as i64;
+    let db = ctx.data().database.pool();
+
+    let config = queries::get_guild_config(db, guild_id).await?;
+    let delete_previous = delete_previous.unwrap_or(config.announce_delete_previous);
+    queries::update_announce_mode(db, guild_id, mode.as_str(), delete_previous).await?;
+
+    let mut description = match mode {
+        AnnounceMode::Off => "Tracks will not be announced".to_string(),
+        AnnounceMode::Compact => "Each track will be announced in a single line".to_string(),
+        AnnounceMode::Full => {
+            "Each track will get a full announcement with player controls".to_string()
+        }
+        AnnounceMode::Edit => {
+            "One player message will be kept up to date for the whole session".to_string()
+        }
+    };
+
+    if delete_previous && matches!(mode, AnnounceMode::Compact | AnnounceMode::Full) {
+        description.push_str("\nPrevious announcements will be deleted when the next track starts");
+    }
+
+    if !config.announce_songs && mode != AnnounceMode::Off {
+        description.push_str(
+            "\nAnnouncements are currently disabled, turn them on with `/config announce`",
+        );
+    }
+
     let song_emoji = get_emoji(ctx.serenity_context(), "song").await;
     let embed = serenity::CreateEmbed::default()
         .title(format!(
@@ -256,6 +781,181 @@ async fn filters(
     Ok(())
 }
 
+/// Manage which links can be played on this server
+#[poise::command(
+    slash_command,
+    subcommands("urls_allow", "urls_deny", "urls_remove", "urls_list", "urls_http")
+)]
+async fn urls(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Only allow links from this domain (and any other allowed domains)
+#[poise::command(slash_command, rename = "allow")]
+async fn urls_allow(
+    ctx: Context<'_>,
+    #[description = "Domain to allow, e.g. youtube.com"] domain: String,
+) -> Result<(), Error> {
+    set_url_rule(ctx, &domain, RULE_ALLOW).await
+}
+
+/// Block links from this domain
+#[poise::command(slash_command, rename = "deny")]
+async fn urls_deny(
+    ctx: Context<'_>,
+    #[description = "Domain to block, e.g. example.com"] domain: String,
+) -> Result<(), Error> {
+    set_url_rule(ctx, &domain, RULE_DENY).await
+}
+
+async fn set_url_rule(ctx: Context<'_>, domain: &str, rule: &str) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?.get() as i64;
+    let db = ctx.data().database.pool();
+
+    let Some(domain) = url_policy::normalize_domain(domain) else {
+        let error_emoji = get_emoji(ctx.serenity_context(), "cross").await;
+        let embed = serenity::CreateEmbed::default()
+            .title(format!(
+                "{} Invalid Domain",
+                error_emoji.unwrap_or_default()
+            ))
+            .description("Please provide a domain such as `youtube.com`.")
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    queries::set_url_rule(db, guild_id, &domain, rule, ctx.author().id.get() as i64).await?;
+
+    let description = if rule == RULE_ALLOW {
+        format!(
+            "Links from `{domain}` are now allowed. Domains without an allow rule will be rejected."
+        )
+    } else {
+        format!("Links from `{domain}` are now blocked")
+    };
+
+    let success_emoji = get_emoji(ctx.serenity_context(), "check").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Link Rules Updated",
+            success_emoji.unwrap_or_default()
+        ))
+        .description(description)
+        .color(COLOR_SUCCESS);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Remove the allow/deny rule for a domain
+#[poise::command(slash_command, rename = "remove")]
+async fn urls_remove(
+    ctx: Context<'_>,
+    #[description = "Domain to remove the rule for"] domain: String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?.get() as i64;
+    let db = ctx.data().database.pool();
+
+    let domain = url_policy::normalize_domain(&domain).unwrap_or(domain);
+    let removed = queries::remove_url_rule(db, guild_id, &domain).await?;
+
+    let embed = if removed {
+        let success_emoji = get_emoji(ctx.serenity_context(), "check").await;
+        serenity::CreateEmbed::default()
+            .title(format!(
+                "{} Link Rules Updated",
+                success_emoji.unwrap_or_default()
+            ))
+            .description(format!("Removed the rule for `{domain}`"))
+            .color(COLOR_SUCCESS)
+    } else {
+        let error_emoji = get_emoji(ctx.serenity_context(), "cross").await;
+        serenity::CreateEmbed::default()
+            .title(format!(
+                "{} Rule Not Found",
+                error_emoji.unwrap_or_default()
+            ))
+            .description(format!("There is no rule for `{domain}`"))
+            .color(COLOR_ERROR)
+    };
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// List the link rules of this server
+#[poise::command(slash_command, rename = "list")]
+async fn urls_list(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?.get() as i64;
+    let db = ctx.data().database.pool();
+
+    let config = queries::get_guild_config(db, guild_id).await?;
+    let rules = queries::get_url_rules(db, guild_id).await?;
+
+    let format_rules = |kind: &str| {
+        let domains = rules
+            .iter()
+            .filter(|r| r.rule == kind)
+            .map(|r| format!("`{}`", r.domain))
+            .collect::<Vec<_>>();
+
+        if domains.is_empty() {
+            "None".to_string()
+        } else {
+            domains.join(", ")
+        }
+    };
+
+    let embed = serenity::CreateEmbed::default()
+        .title("Link Rules")
+        .description(
+            "Blocked domains always win. If any domain is allowed, every other domain is rejected.",
+        )
+        .field("Allowed Domains", format_rules(RULE_ALLOW), false)
+        .field("Blocked Domains", format_rules(RULE_DENY), false)
+        .field(
+            "Allow http:// Links",
+            config.allow_http_urls.to_string(),
+            false,
+        )
+        .color(COLOR_INFO);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Allow or block unencrypted http:// links
+#[poise::command(slash_command, rename = "http")]
+async fn urls_http(
+    ctx: Context<'_>,
+    #[description = "Allow plain http:// links and streams"] enabled: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?.get() as i64;
+    let db = ctx.data().database.pool();
+
+    queries::update_http_urls_setting(db, guild_id, enabled).await?;
+
+    let description = if enabled {
+        "Plain `http://` links can be played again"
+    } else {
+        "Only `https://` links can be played now"
+    };
+
+    let success_emoji = get_emoji(ctx.serenity_context(), "check").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Link Rules Updated",
+            success_emoji.unwrap_or_default()
+        ))
+        .description(description)
+        .color(COLOR_SUCCESS);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
 /// Reset all settings to default
 #[poise::command(slash_command)]
 async fn reset(ctx: Context<'_>) -> Result<(), Error> {
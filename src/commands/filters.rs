@@ -5,6 +5,8 @@ use crate::{
         emojis::get_emoji,
         filters::FilterPreset,
         permissions,
+        player_data::PlayerData,
+        volume,
     },
 };
 use lavalink_rs::model::player::{Filters, Timescale};
@@ -31,6 +33,13 @@ async fn filter_autocomplete(
         .collect()
 }
 
+/// Boosting filters on top of a high volume could go past the server's limit.
+fn lowered_note(lowered: Option<u16>) -> String {
+    lowered.map_or_else(String::new, |vol| {
+        format!("\n\nVolume lowered to {vol}% to stay under this server's limit")
+    })
+}
+
 /// Apply audio filters to the current track
 #[poise::command(slash_command, subcommands("apply", "list", "clear", "custom"))]
 pub async fn filter(_ctx: Context<'_>) -> Result<(), Error> {
@@ -95,13 +104,16 @@ async fn apply(
     let filter_preset = preset.parse::<FilterPreset>()?;
     let filters = filter_preset.to_filters();
     player.set_filters(filters).await?;
+    player.data::<PlayerData>()?.state().filter = Some(filter_preset.name().to_string());
+    let lowered = volume::enforce(&player, volume::guild_limit(&config)).await?;
 
     let embed = serenity::CreateEmbed::default()
         .title(format!("{} Filter Applied", filter_preset.emoji()))
         .description(format!(
-            "**{}**\n{}",
+            "**{}**\n{}{}",
             filter_preset.name(),
-            filter_preset.description()
+            filter_preset.description(),
+            lowered_note(lowered)
         ))
         .color(COLOR_INFO)
         .footer(serenity::CreateEmbedFooter::new(
@@ -170,6 +182,7 @@ async fn clear(ctx: Context<'_>) -> Result<(), Error> {
         .ok_or("Not connected to voice channel")?;
 
     player.set_filters(Filters::default()).await?;
+    player.data::<PlayerData>()?.state().filter = None;
 
     let embed = serenity::CreateEmbed::default()
         .title(format!(
@@ -294,13 +307,15 @@ async fn custom(
     }
 
     player.set_filters(filters).await?;
+    player.data::<PlayerData>()?.state().filter = Some("Custom".to_string());
+    let lowered = volume::enforce(&player, volume::guild_limit(&config)).await?;
 
     let embed = serenity::CreateEmbed::default()
         .title(format!(
             "{} Custom Filter Applied",
             check_emoji.unwrap_or_default()
         ))
-        .description(changes.join("\n"))
+        .description(format!("{}{}", changes.join("\n"), lowered_note(lowered)))
         .color(COLOR_INFO)
         .footer(serenity::CreateEmbedFooter::new(
             "Use /filter clear to remove",
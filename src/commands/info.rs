@@ -4,6 +4,7 @@ use poise::serenity_prelude as serenity;
 /// Display bot info
 #[poise::command(slash_command)]
 pub async fn info(ctx: Context<'_>) -> Result<(), Error> {
+    let bot_id = ctx.cache().current_user().id;
     let embed = serenity::CreateEmbed::default()
         .title("BeatBot information")
         // .description("BeatBot is a music bot powered by Lavalink and written in Rust.")
@@ -14,7 +15,7 @@ pub async fn info(ctx: Context<'_>) -> Result<(), Error> {
         )
         .field(
             "Invite Link",
-            "[Click Here](https://discord.com/oauth2/authorize?client_id=1459592284202078319)",
+            format!("[Click Here](https://discord.com/oauth2/authorize?client_id={bot_id})"),
             true,
         )
         .field(
@@ -2,20 +2,28 @@ use crate::{
     Context, Error,
     utils::{
         constants::{COLOR_ERROR, COLOR_INFO, COLOR_SUCCESS},
-        voicechannel::_join,
+        permissions,
+        voicechannel::{_join, move_to},
     },
 };
 use poise::serenity_prelude as serenity;
 
-/// Join the voice channel
+/// Join the voice channel, or move the bot to another one
 #[poise::command(slash_command)]
-pub async fn join(ctx: Context<'_>) -> Result<(), Error> {
+pub async fn join(
+    ctx: Context<'_>,
+    #[description = "Channel to join or move to (default: your voice channel)"]
+    #[channel_types("Voice", "Stage")]
+    channel: Option<serenity::GuildChannel>,
+) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap();
     let cache = ctx.cache();
-    let user_voice = cache.guild(guild_id).and_then(|g| {
-        g.voice_states
-            .get(&ctx.author().id)
-            .and_then(|vs| vs.channel_id)
+    let user_voice = channel.as_ref().map(|c| c.id).or_else(|| {
+        cache.guild(guild_id).and_then(|g| {
+            g.voice_states
+                .get(&ctx.author().id)
+                .and_then(|vs| vs.channel_id)
+        })
     });
     let me_voice = cache.guild(guild_id).and_then(|g| {
         g.voice_states
@@ -28,7 +36,7 @@ pub async fn join(ctx: Context<'_>) -> Result<(), Error> {
     if user_voice.is_none() {
         let embed = serenity::CreateEmbed::default()
             .title(format!("{} Cannot Join", error_emoji.unwrap_or_default()))
-            .description("You must be in a voice channel to use this command.")
+            .description("Pick a channel or join a voice channel first.")
             .color(COLOR_ERROR);
 
         ctx.send(poise::CreateReply::default().embed(embed)).await?;
@@ -41,18 +49,64 @@ pub async fn join(ctx: Context<'_>) -> Result<(), Error> {
                 "{} Already Connected",
                 success_emoji.unwrap_or_default()
             ))
-            .description("I am already in your voice channel!")
+            .description(format!("I am already in <#{}>!", user_voice.unwrap()))
             .color(COLOR_INFO);
 
         ctx.send(poise::CreateReply::default().embed(embed)).await?;
         return Ok(());
     }
 
-    if me_voice != user_voice && me_voice.is_some() {
+    if let Some(current) = me_voice
+        && me_voice != user_voice
+    {
+        if channel.is_none() {
+            let embed = serenity::CreateEmbed::default()
+                .title(format!("{} Busy", alert_emoji.unwrap_or_default()))
+                .description(
+                    "I am currently in another voice channel. Use `/join channel:` to move me.",
+                )
+                .color(COLOR_ERROR);
+
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+
+        if !permissions::check_dj_or_admin(ctx).await? {
+            let embed = serenity::CreateEmbed::default()
+                .title(format!(
+                    "{} Permission Denied",
+                    error_emoji.unwrap_or_default()
+                ))
+                .description("You need the DJ role or admin permissions to move the bot.")
+                .color(COLOR_ERROR);
+
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+
+        move_to(
+            ctx.serenity_context(),
+            ctx.data(),
+            guild_id,
+            user_voice.unwrap(),
+        )
+        .await?;
+
         let embed = serenity::CreateEmbed::default()
-            .title(format!("{} Busy", alert_emoji.unwrap_or_default()))
-            .description("I am currently in another voice channel.")
-            .color(COLOR_ERROR);
+            .title(format!(
+                "{} Moved Voice Channel",
+                success_emoji.unwrap_or_default()
+            ))
+            .description(format!(
+                "Moved from <#{}> to <#{}>",
+                current,
+                user_voice.unwrap()
+            ))
+            .color(COLOR_SUCCESS)
+            .footer(serenity::CreateEmbedFooter::new(format!(
+                "Requested by {}",
+                ctx.author().name
+            )));
 
         ctx.send(poise::CreateReply::default().embed(embed)).await?;
         return Ok(());
@@ -1,6 +1,6 @@
 use crate::{
     Context, Error,
-    utils::{constants::COLOR_SUCCESS, emojis},
+    utils::{constants::COLOR_SUCCESS, emojis, voicechannel::_leave},
 };
 use poise::serenity_prelude as serenity;
 
@@ -12,11 +12,8 @@ pub async fn leave(ctx: Context<'_>) -> Result<(), Error> {
     let manager = songbird::get(ctx.serenity_context()).await.unwrap().clone();
     let lava_client = ctx.data().lavalink.clone();
     let success_emoji = emojis::get_emoji(ctx.serenity_context(), "check").await;
-    lava_client.delete_player(guild_id).await?;
+    _leave(&lava_client, &manager, guild_id).await?;
 
-    if manager.get(guild_id).is_some() {
-        manager.remove(guild_id).await?;
-    }
     let embed = serenity::CreateEmbed::default()
         .title(format!(
             "{} Left voice channel",
@@ -4,12 +4,16 @@ pub mod filters;
 pub mod info;
 pub mod join;
 pub mod leave;
+pub mod nowplaying;
 pub mod pause;
 pub mod play;
 pub mod queue;
 pub mod remove;
+pub mod restore;
 pub mod resume;
+pub mod schedule;
 pub mod seek;
 pub mod skip;
+pub mod sleep;
 pub mod stop;
 pub mod volume;
@@ -0,0 +1,56 @@
+use crate::{
+    Context, Error,
+    utils::{
+        announcements::AnnouncementBuilder,
+        constants::COLOR_ERROR,
+        player_data::PlayerData,
+        player_message::{self, PlayerStatus},
+    },
+};
+use poise::serenity_prelude as serenity;
+
+/// Show the track that is currently playing
+#[poise::command(slash_command, prefix_command, aliases("np"), guild_only)]
+pub async fn nowplaying(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let lava_client = ctx.data().lavalink.clone();
+    let error_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "cross").await;
+
+    let Some(player) = lava_client.get_player_context(guild_id) else {
+        let embed = serenity::CreateEmbed::default()
+            .title(format!("{} Not Connected", error_emoji.unwrap_or_default()))
+            .description("Join the bot to a voice channel first.")
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    let data = player.data::<PlayerData>()?;
+    let (track, status) = PlayerStatus::fetch(&player, &data).await?;
+
+    let Some(track) = track else {
+        let embed = serenity::CreateEmbed::default()
+            .title(format!(
+                "{} Nothing Playing",
+                error_emoji.unwrap_or_default()
+            ))
+            .description("No track is currently playing.")
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    let embed =
+        AnnouncementBuilder::now_playing(&track, player_message::requester_id(&track), &status);
+
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .components(player_message::shortcuts()),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -3,6 +3,9 @@ use crate::{
     database::queries,
     utils::{
         constants::{COLOR_ERROR, COLOR_INFO, COLOR_SPOTIFY},
+        stay,
+        time::{format_duration, parse_timestamp, url_start_time},
+        url_policy::{self, UrlVerdict},
         voicechannel::_join,
     },
 };
@@ -10,6 +13,7 @@ use ::serenity::all::Mentionable;
 use lavalink_rs::prelude::*;
 use poise::serenity_prelude as serenity;
 use serenity::all::AutocompleteChoice;
+use std::time::Duration;
 
 async fn play_autocomplete(ctx: Context<'_>, partial: &str) -> Vec<AutocompleteChoice> {
     let lava_client = ctx.data().lavalink.clone();
@@ -19,7 +23,7 @@ async fn play_autocomplete(ctx: Context<'_>, partial: &str) -> Vec<AutocompleteC
         return choices;
     }
 
-    let query = if partial.starts_with("http") {
+    let query = if url_policy::is_link(partial) {
         partial.to_string()
     } else {
         match SearchEngines::Spotify.to_query(partial) {
@@ -55,17 +59,13 @@ async fn play_autocomplete(ctx: Context<'_>, partial: &str) -> Vec<AutocompleteC
     choices
 }
 
-fn format_duration(ms: u64) -> String {
-    let seconds = ms / 1000;
-    let minutes = seconds / 60;
-    let hours = minutes / 60;
-
-    if hours > 0 {
-        format!("{hours}:{:02}:{:02}", minutes % 60, seconds % 60)
-    } else {
-        format!("{minutes}:{:02}", seconds % 60)
-    }
-}
+const MAX_ATTACHMENT_SIZE: u32 = 50 * 1024 * 1024;
+const EXTRA_AUDIO_MIME_TYPES: [&str; 4] = [
+    "application/ogg",
+    "video/mp4",
+    "video/webm",
+    "video/quicktime",
+];
 
 /// Play a track or playlist
 #[poise::command(slash_command)]
@@ -73,12 +73,181 @@ pub async fn play(
     ctx: Context<'_>,
     #[description = "The search query or URL to play"]
     #[autocomplete = "play_autocomplete"]
-    term: String,
+    term: Option<String>,
+    #[description = "An audio file to play"] attachment: Option<serenity::Attachment>,
+    #[description = "Start playback at this position, e.g. 1:30 or 1m30s"] start: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let error_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "cross").await;
+
+    let start_time = match start.as_deref().map(parse_timestamp) {
+        Some(None) => {
+            let embed = serenity::CreateEmbed::default()
+                .title(format!(
+                    "{} Invalid Start Time",
+                    error_emoji.unwrap_or_default()
+                ))
+                .description("Use a position like `90`, `1:30` or `1m30s`.")
+                .color(COLOR_ERROR);
+
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+        Some(start_time) => start_time,
+        None => None,
+    };
+
+    if let Some(attachment) = attachment {
+        return play_file(ctx, &attachment, start_time).await;
+    }
+
+    let Some(term) = term else {
+        let embed = serenity::CreateEmbed::default()
+            .title(format!(
+                "{} Nothing to Play",
+                error_emoji.unwrap_or_default()
+            ))
+            .description("Provide a search term, a link or an audio file.")
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    if url_policy::is_link(&term)
+        && let UrlVerdict::Blocked(reason) =
+            url_policy::check_url(ctx.data().database.pool(), guild_id.get() as i64, &term).await?
+    {
+        let embed = serenity::CreateEmbed::default()
+            .title(format!(
+                "{} Link Not Allowed",
+                error_emoji.unwrap_or_default()
+            ))
+            .description(reason)
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    let (query, start_time) = if url_policy::is_link(&term) {
+        let start_time = start_time.or_else(|| url_start_time(&term));
+        (term, start_time)
+    } else {
+        (SearchEngines::Spotify.to_query(&term)?, start_time)
+    };
+
+    enqueue(ctx, &query, None, start_time).await
+}
+
+/// Play the audio file attached to this message
+#[poise::command(context_menu_command = "Play attachment", guild_only)]
+pub async fn play_attachment(
+    ctx: Context<'_>,
+    #[description = "Message with an audio file"] msg: serenity::Message,
+) -> Result<(), Error> {
+    let attachment = msg
+        .attachments
+        .iter()
+        .find(|a| validate_attachment(a).is_ok())
+        .or(msg.attachments.first());
+
+    let Some(attachment) = attachment else {
+        let error_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "cross").await;
+        let embed = serenity::CreateEmbed::default()
+            .title(format!("{} No Attachment", error_emoji.unwrap_or_default()))
+            .description("That message has no audio file attached.")
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+        return Ok(());
+    };
+
+    play_file(ctx, attachment, None).await
+}
+
+async fn play_file(
+    ctx: Context<'_>,
+    attachment: &serenity::Attachment,
+    start_time: Option<Duration>,
+) -> Result<(), Error> {
+    if let Err(reason) = validate_attachment(attachment) {
+        let error_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "cross").await;
+        let embed = serenity::CreateEmbed::default()
+            .title(format!(
+                "{} Unsupported File",
+                error_emoji.unwrap_or_default()
+            ))
+            .description(reason)
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    // Probing a file over HTTP can take longer than Discord's 3 second reply window.
+    ctx.defer().await?;
+
+    enqueue(
+        ctx,
+        &attachment.url,
+        Some(attachment_title(&attachment.filename)),
+        start_time,
+    )
+    .await
+}
+
+fn validate_attachment(attachment: &serenity::Attachment) -> Result<(), String> {
+    let mime = attachment
+        .content_type
+        .as_deref()
+        .and_then(|c| c.split(';').next())
+        .unwrap_or_default()
+        .trim();
+
+    if !mime.starts_with("audio/") && !EXTRA_AUDIO_MIME_TYPES.contains(&mime) {
+        return Err(format!(
+            "`{}` is not an audio file I can play.",
+            attachment.filename
+        ));
+    }
+
+    if attachment.size > MAX_ATTACHMENT_SIZE {
+        return Err(format!(
+            "`{}` is too large. Audio files can be at most {} MB.",
+            attachment.filename,
+            MAX_ATTACHMENT_SIZE / 1024 / 1024
+        ));
+    }
+
+    Ok(())
+}
+
+fn attachment_title(filename: &str) -> String {
+    let stem = filename
+        .rsplit_once('.')
+        .map_or(filename, |(stem, _)| stem)
+        .replace('_', " ");
+    let stem = stem.trim();
+
+    if stem.is_empty() {
+        filename.to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+async fn enqueue(
+    ctx: Context<'_>,
+    query: &str,
+    title: Option<String>,
+    start_time: Option<Duration>,
 ) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap();
+    let error_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "cross").await;
     let has_joined = _join(&ctx, guild_id, None).await?;
     let lava_client = ctx.data().lavalink.clone();
-    let error_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "cross").await;
     let _success_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "check").await;
     let playlist_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "album").await;
     let player_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "player").await;
@@ -97,13 +266,7 @@ pub async fn play(
         return Ok(());
     };
 
-    let query = if term.starts_with("http") {
-        term
-    } else {
-        SearchEngines::Spotify.to_query(&term)?
-    };
-
-    let loaded_tracks = lava_client.load_tracks(guild_id, &query).await?;
+    let loaded_tracks = lava_client.load_tracks(guild_id, query).await?;
 
     let mut playlist_info = None;
 
@@ -125,6 +288,12 @@ pub async fn play(
         }
     };
 
+    if let Some(title) = &title {
+        for i in &mut tracks {
+            i.track.info.title = title.clone();
+        }
+    }
+
     if player.get_queue().get_count().await.unwrap() as i32 >= max_queue {
         let embed = serenity::CreateEmbed::default()
             .title(format!(
@@ -139,7 +308,7 @@ pub async fn play(
         ctx.send(poise::CreateReply::default().embed(embed)).await?;
         return Ok(());
     }
-    if let Some(info) = playlist_info {
+    if let Some(info) = &playlist_info {
         let embed = serenity::CreateEmbed::default()
             .title(format!(
                 "{} Playlist Added",
@@ -179,6 +348,14 @@ pub async fn play(
             .field("Requested by", ctx.author().mention().to_string(), true)
             .color(COLOR_SPOTIFY);
 
+        if let Some(start_time) = start_time {
+            embed = embed.field(
+                "Starts at",
+                format_duration(start_time.as_millis() as u64),
+                true,
+            );
+        }
+
         if let Some(artwork) = &track.info.artwork_url {
             embed = embed.thumbnail(artwork);
         }
@@ -187,9 +364,19 @@ pub async fn play(
     }
 
     for i in &mut tracks {
-        i.track.user_data = Some(serde_json::json!({"requester_id": ctx.author().id.get()}));
+        let mut user_data = serde_json::json!({"requester_id": ctx.author().id.get()});
+        if let Some(title) = &title {
+            user_data["title"] = serde_json::json!(title);
+        }
+        if let Some(start_time) = start_time
+            && playlist_info.is_none()
+        {
+            user_data["start_time"] = serde_json::json!(start_time.as_millis() as u64);
+        }
+        i.track.user_data = Some(user_data);
     }
 
+    stay::drop_fallback(&player).await?;
     let queue = player.get_queue();
     queue.append(tracks.into())?;
 
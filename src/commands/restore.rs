@@ -0,0 +1,86 @@
+use crate::{
+    Context, Error,
+    utils::{
+        constants::{COLOR_ERROR, COLOR_SUCCESS},
+        permissions, saved_queue,
+        voicechannel::_join,
+    },
+};
+use poise::serenity_prelude as serenity;
+
+/// Bring back the queue saved when the bot was disconnected
+#[poise::command(slash_command)]
+pub async fn restore(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let error_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "cross").await;
+    let success_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "check").await;
+
+    if !permissions::check_dj_or_admin(ctx).await? {
+        let embed = serenity::CreateEmbed::default()
+            .title(format!(
+                "{} Permission Denied",
+                error_emoji.unwrap_or_default()
+            ))
+            .description("You need the DJ role or admin permissions to use this command.")
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    if !permissions::check_in_voice(ctx).await? {
+        let embed = serenity::CreateEmbed::default()
+            .title(format!(
+                "{} Not in Voice Channel",
+                error_emoji.unwrap_or_default()
+            ))
+            .description("You must be in the same voice channel as the bot.")
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    _join(&ctx, guild_id, None).await?;
+
+    let Some(player) = ctx.data().lavalink.get_player_context(guild_id) else {
+        let embed = serenity::CreateEmbed::default()
+            .title(format!("{} Not Connected", error_emoji.unwrap_or_default()))
+            .description("I couldn't join your voice channel.")
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    let restored = saved_queue::restore(&player, ctx.data().database.pool(), guild_id).await?;
+
+    if restored == 0 {
+        let embed = serenity::CreateEmbed::default()
+            .title(format!(
+                "{} No Saved Queue",
+                error_emoji.unwrap_or_default()
+            ))
+            .description("There is no saved queue for this server.")
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Queue Restored",
+            success_emoji.unwrap_or_default()
+        ))
+        .description(format!("Added {restored} tracks back to the queue."))
+        .color(COLOR_SUCCESS)
+        .footer(serenity::CreateEmbedFooter::new(format!(
+            "Requested by {}",
+            ctx.author().name
+        )));
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
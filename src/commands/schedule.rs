@@ -0,0 +1,246 @@
+use crate::{
+    Context, Error,
+    database::queries,
+    utils::{
+        constants::{COLOR_ERROR, COLOR_INFO, COLOR_SUCCESS},
+        emojis::get_emoji,
+        schedule::{self, Repeat},
+    },
+};
+use poise::serenity_prelude as serenity;
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("create", "list", "edit", "cancel")
+)]
+pub async fn schedule(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+async fn send_error(ctx: Context<'_>, title: &str, description: String) -> Result<(), Error> {
+    let error_emoji = get_emoji(ctx.serenity_context(), "cross").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!("{} {title}", error_emoji.unwrap_or_default()))
+        .description(description)
+        .color(COLOR_ERROR);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Play a guild playlist in a voice channel at a set time
+#[poise::command(slash_command)]
+async fn create(
+    ctx: Context<'_>,
+    #[description = "Guild playlist to play"] playlist: String,
+    #[description = "Voice channel to play in"]
+    #[channel_types("Voice", "Stage")]
+    channel: serenity::GuildChannel,
+    #[description = "UTC date and time like 2026-02-14 20:00, or how long from now like 2h"]
+    time: String,
+    #[description = "Play it again every day or week"] repeat: Option<Repeat>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?.get() as i64;
+    let db = ctx.data().database.pool();
+    let now = chrono::Utc::now().timestamp();
+
+    let Some(found) = queries::get_guild_playlist_by_name(db, guild_id, &playlist).await? else {
+        return send_error(
+            ctx,
+            "Playlist Not Found",
+            format!("This server has no playlist named **{playlist}**."),
+        )
+        .await;
+    };
+
+    let Some(starts_at) = schedule::parse_start(&time, now).filter(|t| *t > now) else {
+        return send_error(
+            ctx,
+            "Invalid Time",
+            "Use a future UTC time like `2026-02-14 20:00`, or a duration like `2h`.".to_string(),
+        )
+        .await;
+    };
+
+    let created = queries::create_schedule(
+        db,
+        guild_id,
+        found.id,
+        channel.id.get() as i64,
+        starts_at,
+        repeat.unwrap_or(Repeat::Never).as_str(),
+        ctx.author().id.get() as i64,
+    )
+    .await?;
+
+    let clock_emoji = get_emoji(ctx.serenity_context(), "clock").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Playback Scheduled",
+            clock_emoji.unwrap_or_default()
+        ))
+        .description(schedule::describe(&created, &found.name))
+        .color(COLOR_SUCCESS);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// List the scheduled playbacks
+#[poise::command(slash_command)]
+async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?.get() as i64;
+    let db = ctx.data().database.pool();
+
+    let schedules = queries::get_guild_schedules(db, guild_id).await?;
+    let playlists = queries::get_guild_playlists(db, guild_id).await?;
+
+    let description = if schedules.is_empty() {
+        "Nothing is scheduled. Add something with `/schedule create`.".to_string()
+    } else {
+        schedules
+            .iter()
+            .map(|s| {
+                let name = playlists
+                    .iter()
+                    .find(|p| p.id == s.playlist_id)
+                    .map_or("missing playlist", |p| p.name.as_str());
+                schedule::describe(s, name)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let clock_emoji = get_emoji(ctx.serenity_context(), "clock").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Scheduled Playbacks",
+            clock_emoji.unwrap_or_default()
+        ))
+        .description(description)
+        .color(COLOR_INFO);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Change a scheduled playback
+#[poise::command(slash_command)]
+async fn edit(
+    ctx: Context<'_>,
+    #[description = "Schedule number, as shown in /schedule list"] id: i64,
+    #[description = "Guild playlist to play"] playlist: Option<String>,
+    #[description = "Voice channel to play in"]
+    #[channel_types("Voice", "Stage")]
+    channel: Option<serenity::GuildChannel>,
+    #[description = "UTC date and time like 2026-02-14 20:00, or how long from now like 2h"]
+    time: Option<String>,
+    #[description = "Play it again every day or week"] repeat: Option<Repeat>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?.get() as i64;
+    let db = ctx.data().database.pool();
+    let now = chrono::Utc::now().timestamp();
+
+    let Some(mut scheduled) = queries::get_schedule(db, id, guild_id).await? else {
+        return send_error(
+            ctx,
+            "Schedule Not Found",
+            format!("There is no schedule `#{id}`."),
+        )
+        .await;
+    };
+
+    let found = match playlist {
+        Some(name) => match queries::get_guild_playlist_by_name(db, guild_id, &name).await? {
+            Some(found) => Some(found),
+            None => {
+                return send_error(
+                    ctx,
+                    "Playlist Not Found",
+                    format!("This server has no playlist named **{name}**."),
+                )
+                .await;
+            }
+        },
+        None => None,
+    };
+
+    if let Some(time) = time {
+        let Some(starts_at) = schedule::parse_start(&time, now).filter(|t| *t > now) else {
+            return send_error(
+                ctx,
+                "Invalid Time",
+                "Use a future UTC time like `2026-02-14 20:00`, or a duration like `2h`."
+                    .to_string(),
+            )
+            .await;
+        };
+        scheduled.starts_at = starts_at;
+    }
+
+    if let Some(found) = &found {
+        scheduled.playlist_id = found.id;
+    }
+    if let Some(channel) = channel {
+        scheduled.voice_channel_id = channel.id.get() as i64;
+    }
+    if let Some(repeat) = repeat {
+        scheduled.repeat_every = repeat.as_str().to_string();
+    }
+
+    queries::update_schedule(db, &scheduled).await?;
+
+    let name = match found {
+        Some(found) => found.name,
+        None => queries::get_guild_playlists(db, guild_id)
+            .await?
+            .into_iter()
+            .find(|p| p.id == scheduled.playlist_id)
+            .map_or_else(|| "missing playlist".to_string(), |p| p.name),
+    };
+
+    let check_emoji = get_emoji(ctx.serenity_context(), "check").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Schedule Updated",
+            check_emoji.unwrap_or_default()
+        ))
+        .description(schedule::describe(&scheduled, &name))
+        .color(COLOR_SUCCESS);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Cancel a scheduled playback
+#[poise::command(slash_command)]
+async fn cancel(
+    ctx: Context<'_>,
+    #[description = "Schedule number, as shown in /schedule list"] id: i64,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Must be in a guild")?.get() as i64;
+    let db = ctx.data().database.pool();
+
+    if !queries::delete_schedule(db, id, guild_id).await? {
+        return send_error(
+            ctx,
+            "Schedule Not Found",
+            format!("There is no schedule `#{id}`."),
+        )
+        .await;
+    }
+
+    let check_emoji = get_emoji(ctx.serenity_context(), "check").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Schedule Cancelled",
+            check_emoji.unwrap_or_default()
+        ))
+        .description(format!("Schedule `#{id}` has been cancelled."))
+        .color(COLOR_SUCCESS);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
@@ -3,43 +3,134 @@ use std::time::Duration;
 
 use crate::{
     Context, Error,
-    utils::constants::{COLOR_ERROR, COLOR_SUCCESS},
+    utils::{
+        constants::{COLOR_ERROR, COLOR_SUCCESS},
+        permissions,
+        time::{SeekTarget, estimated_position, format_duration, parse_timestamp},
+    },
 };
 
-/// Jump to a specific time in the song, in seconds.
+const DEFAULT_JUMP: Duration = Duration::from_secs(10);
+
+/// Jump to a position in the song, e.g. 1:23, 1h2m, +30, -15 or 50%
 #[poise::command(slash_command, prefix_command)]
 pub async fn seek(
     ctx: Context<'_>,
-    #[description = "Time to jump to (in seconds)"] time: u64,
+    #[description = "Position like 1:23 or 1h2m, a jump like +30 or -15, or a percentage like 50%"]
+    time: String,
+) -> Result<(), Error> {
+    let Some(target) = SeekTarget::parse(&time) else {
+        let error_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "cross").await;
+        let embed = serenity::CreateEmbed::default()
+            .title(format!("{} Invalid Position", error_emoji.unwrap_or_default()))
+            .description(
+                "Use a position like `1:23` or `1h2m`, a jump like `+30` or `-15`, or a percentage like `50%`.",
+            )
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    seek_to(ctx, target).await
+}
+
+/// Skip ahead in the current song
+#[poise::command(slash_command, prefix_command)]
+pub async fn forward(
+    ctx: Context<'_>,
+    #[description = "How far to skip ahead, e.g. 30 or 1m (default: 10s)"] amount: Option<String>,
+) -> Result<(), Error> {
+    let Some(amount) = parse_jump(ctx, amount.as_deref()).await? else {
+        return Ok(());
+    };
+
+    seek_to(ctx, SeekTarget::Forward(amount)).await
+}
+
+/// Go back in the current song
+#[poise::command(slash_command, prefix_command)]
+pub async fn rewind(
+    ctx: Context<'_>,
+    #[description = "How far to go back, e.g. 30 or 1m (default: 10s)"] amount: Option<String>,
 ) -> Result<(), Error> {
+    let Some(amount) = parse_jump(ctx, amount.as_deref()).await? else {
+        return Ok(());
+    };
+
+    seek_to(ctx, SeekTarget::Backward(amount)).await
+}
+
+async fn parse_jump(ctx: Context<'_>, amount: Option<&str>) -> Result<Option<Duration>, Error> {
+    let Some(amount) = amount else {
+        return Ok(Some(DEFAULT_JUMP));
+    };
+
+    if let Some(amount) = parse_timestamp(amount) {
+        return Ok(Some(amount));
+    }
+
+    let error_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "cross").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Invalid Amount",
+            error_emoji.unwrap_or_default()
+        ))
+        .description("Use an amount like `30`, `1:30` or `1m30s`.")
+        .color(COLOR_ERROR);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(None)
+}
+
+async fn seek_to(ctx: Context<'_>, target: SeekTarget) -> Result<(), Error> {
     let guild_id = ctx.guild_id().unwrap();
 
     let lava_client = ctx.data().lavalink.clone();
+    let is_dj_or_admin = permissions::check_dj_or_admin(ctx).await?;
+    let check_in_voice = permissions::check_in_voice(ctx).await?;
     let error_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "cross").await;
-    let clock_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "clock");
-    let Some(player) = lava_client.get_player_context(guild_id) else {
+    let clock_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "clock").await;
+
+    if !is_dj_or_admin {
         let embed = serenity::CreateEmbed::default()
-            .title(format!("{} Not Connected", error_emoji.unwrap_or_default()))
-            .description("Join the bot to a voice channel first.")
+            .title(format!(
+                "{} Permission Denied",
+                error_emoji.unwrap_or_default()
+            ))
+            .description("You need the DJ role or admin permissions to use this command.")
             .color(COLOR_ERROR);
 
         ctx.send(poise::CreateReply::default().embed(embed)).await?;
         return Ok(());
-    };
-
-    let now_playing = player.get_player().await?.track;
+    }
 
-    if now_playing.is_some() {
-        player.set_position(Duration::from_secs(time)).await?;
+    if !check_in_voice {
         let embed = serenity::CreateEmbed::default()
             .title(format!(
-                "{} Jumped to {time}s",
-                clock_emoji.await.unwrap_or_default(),
+                "{} Not in Voice Channel",
+                error_emoji.unwrap_or_default()
             ))
-            .color(COLOR_SUCCESS);
+            .description("You must be in the same voice channel as the bot.")
+            .color(COLOR_ERROR);
 
         ctx.send(poise::CreateReply::default().embed(embed)).await?;
-    } else {
+        return Ok(());
+    }
+
+    let Some(player) = lava_client.get_player_context(guild_id) else {
+        let embed = serenity::CreateEmbed::default()
+            .title(format!("{} Not Connected", error_emoji.unwrap_or_default()))
+            .description("Join the bot to a voice channel first.")
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    let player_data = player.get_player().await?;
+
+    let Some(track) = &player_data.track else {
         let embed = serenity::CreateEmbed::default()
             .title(format!(
                 "{} Nothing Playing",
@@ -49,7 +140,37 @@ pub async fn seek(
             .color(COLOR_ERROR);
 
         ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    if !track.info.is_seekable || track.info.is_stream {
+        let embed = serenity::CreateEmbed::default()
+            .title(format!("{} Cannot Seek", error_emoji.unwrap_or_default()))
+            .description("The current track is a live stream or does not support seeking.")
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
     }
 
+    let position = target.resolve(estimated_position(&player_data), track.info.length);
+    player.set_position(Duration::from_millis(position)).await?;
+
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Jumped to {}",
+            clock_emoji.unwrap_or_default(),
+            format_duration(position)
+        ))
+        .description(format!(
+            "**{} - {}** ({})",
+            track.info.author,
+            track.info.title,
+            format_duration(track.info.length)
+        ))
+        .color(COLOR_SUCCESS);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
     Ok(())
 }
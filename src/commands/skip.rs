@@ -2,7 +2,8 @@ use crate::{
     Context, Error,
     utils::{
         constants::{COLOR_ERROR, COLOR_SUCCESS},
-        permissions,
+        permissions, playback,
+        player_data::PlayerData,
     },
 };
 use poise::serenity_prelude as serenity;
@@ -56,7 +57,7 @@ pub async fn skip(ctx: Context<'_>) -> Result<(), Error> {
     let np = player.get_player().await?.track;
 
     if let Some(np) = np {
-        player.skip()?;
+        playback::skip(&player, &*player.data::<PlayerData>()?).await?;
 
         let embed = serenity::CreateEmbed::default()
             .title(format!("{} Track Skipped", skip_emoji.unwrap_or_default()))
@@ -0,0 +1,178 @@
+use crate::{
+    Context, Error,
+    database::queries,
+    utils::{
+        constants::{COLOR_ERROR, COLOR_INFO, COLOR_SUCCESS},
+        permissions,
+        sleep::{self, SleepMode},
+        time::parse_timestamp,
+    },
+};
+use poise::serenity_prelude as serenity;
+use std::time::Duration;
+
+/// Stop playback on its own after a while
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands("after", "end_of_track", "end_of_queue", "cancel", "status")
+)]
+pub async fn sleep(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Fade out and stop after a duration
+#[poise::command(slash_command)]
+async fn after(
+    ctx: Context<'_>,
+    #[description = "How long until playback stops, e.g. 30m or 1h30m"] duration: String,
+    #[description = "Leave the voice channel too"] disconnect: Option<bool>,
+) -> Result<(), Error> {
+    let Some(duration) =
+        parse_timestamp(&duration).filter(|d| !d.is_zero() && *d <= sleep::MAX_DURATION)
+    else {
+        let error_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "cross").await;
+        let embed = serenity::CreateEmbed::default()
+            .title(format!(
+                "{} Invalid Duration",
+                error_emoji.unwrap_or_default()
+            ))
+            .description("Use a duration of up to 24 hours, like `45m`, `1h30m` or `1:30:00`.")
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    set_timer(
+        ctx,
+        SleepMode::After,
+        duration,
+        disconnect.unwrap_or_default(),
+    )
+    .await
+}
+
+/// Stop when the current track ends (cleared if the bot restarts)
+#[poise::command(slash_command, rename = "end-of-track")]
+async fn end_of_track(
+    ctx: Context<'_>,
+    #[description = "Leave the voice channel too"] disconnect: Option<bool>,
+) -> Result<(), Error> {
+    set_timer(
+        ctx,
+        SleepMode::EndOfTrack,
+        Duration::ZERO,
+        disconnect.unwrap_or_default(),
+    )
+    .await
+}
+
+/// Stop when the queue runs out (cleared if the bot restarts)
+#[poise::command(slash_command, rename = "end-of-queue")]
+async fn end_of_queue(
+    ctx: Context<'_>,
+    #[description = "Leave the voice channel too"] disconnect: Option<bool>,
+) -> Result<(), Error> {
+    set_timer(
+        ctx,
+        SleepMode::EndOfQueue,
+        Duration::ZERO,
+        disconnect.unwrap_or_default(),
+    )
+    .await
+}
+
+async fn set_timer(
+    ctx: Context<'_>,
+    mode: SleepMode,
+    after: Duration,
+    disconnect: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let error_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "cross").await;
+
+    if !permissions::check_in_voice(ctx).await? {
+        let embed = serenity::CreateEmbed::default()
+            .title(format!(
+                "{} Not in Voice Channel",
+                error_emoji.unwrap_or_default()
+            ))
+            .description("You must be in the same voice channel as the bot.")
+            .color(COLOR_ERROR);
+
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    let timer = ctx
+        .data()
+        .sleep_timers
+        .set(guild_id, mode, after, disconnect, ctx.channel_id())
+        .await?;
+
+    let clock_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "clock").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Sleep Timer Set",
+            clock_emoji.unwrap_or_default()
+        ))
+        .description(sleep::describe(&timer))
+        .color(COLOR_SUCCESS)
+        .footer(serenity::CreateEmbedFooter::new(format!(
+            "Requested by {}",
+            ctx.author().name
+        )));
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Cancel the sleep timer
+#[poise::command(slash_command)]
+async fn cancel(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+
+    let embed = if ctx.data().sleep_timers.cancel(guild_id).await? {
+        let success_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "check").await;
+        serenity::CreateEmbed::default()
+            .title(format!(
+                "{} Sleep Timer Cancelled",
+                success_emoji.unwrap_or_default()
+            ))
+            .description("Playback will carry on as usual.")
+            .color(COLOR_SUCCESS)
+    } else {
+        let error_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "cross").await;
+        serenity::CreateEmbed::default()
+            .title(format!(
+                "{} No Sleep Timer",
+                error_emoji.unwrap_or_default()
+            ))
+            .description("There is no sleep timer to cancel.")
+            .color(COLOR_ERROR)
+    };
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Show the sleep timer
+#[poise::command(slash_command)]
+async fn status(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let timer = queries::get_sleep_timer(ctx.data().database.pool(), guild_id.get() as i64).await?;
+
+    let clock_emoji = crate::utils::emojis::get_emoji(ctx.serenity_context(), "clock").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!("{} Sleep Timer", clock_emoji.unwrap_or_default()))
+        .description(
+            timer
+                .as_ref()
+                .map_or_else(|| "No sleep timer is set.".to_string(), sleep::describe),
+        )
+        .color(COLOR_INFO);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
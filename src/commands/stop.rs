@@ -3,6 +3,8 @@ use crate::{
     utils::{
         constants::{COLOR_ERROR, COLOR_INFO, COLOR_SUCCESS},
         emojis::get_emoji,
+        playback,
+        player_data::PlayerData,
     },
 };
 use poise::serenity_prelude as serenity;
@@ -29,7 +31,7 @@ pub async fn stop(ctx: Context<'_>) -> Result<(), Error> {
     let now_playing = player.get_player().await?.track;
 
     if let Some(np) = now_playing {
-        player.stop_now().await?;
+        playback::stop(&player, &*player.data::<PlayerData>()?).await?;
         let embed = serenity::CreateEmbed::default()
             .title(format!("{} Stopped", stop_emoji.unwrap_or_default()))
             .description(format!("**{} - {}**", np.info.author, np.info.title))
@@ -1,6 +1,10 @@
 use crate::{
     Context, Error,
-    utils::constants::{COLOR_ERROR, COLOR_INFO},
+    database::queries,
+    utils::{
+        constants::{COLOR_ERROR, COLOR_INFO},
+        volume,
+    },
 };
 use poise::serenity_prelude as serenity;
 
@@ -27,11 +31,24 @@ pub async fn volume(
         return Ok(());
     };
 
-    player.set_volume(vol).await?;
+    let db = ctx.data().database.pool();
+    let config = queries::get_guild_config(db, guild_id.get() as i64).await?;
+    let member = ctx.author_member().await.ok_or("Member not found")?;
+    let limit = volume::member_limit(db, &config, &member).await?;
+    let limit = volume::player_limit(&player, limit).await?;
+
+    let set = vol.min(limit);
+    player.set_volume(set).await?;
+
+    let description = if set < vol {
+        format!("Volume set to {set}%, the most you can set on this server right now")
+    } else {
+        format!("Volume set to {set}%")
+    };
 
     let embed = serenity::CreateEmbed::default()
         .title(format!("{} Volume set", vol_emoji.unwrap_or_default()))
-        .description(format!("Volume set to {vol}%"))
+        .description(description)
         .color(COLOR_INFO);
     ctx.send(poise::CreateReply::default().embed(embed)).await?;
 
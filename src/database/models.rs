@@ -15,6 +15,21 @@ pub struct GuildConfig {
     pub allow_explicit: bool,
     pub created_at: String,
     pub updated_at: String,
+    pub allow_http_urls: bool,
+    pub announce_mode: String,
+    pub announce_delete_previous: bool,
+    pub announce_thread: bool,
+    pub pause_when_alone: bool,
+    pub alone_timeout: i32,
+    pub stay_channel_id: Option<i64>,
+    pub fallback_playlist_id: Option<i64>,
+    pub save_queue_on_disconnect: bool,
+    pub voice_status: bool,
+    /// Seconds, 0 turns fades off.
+    pub fade_duration: i32,
+    pub crossfade: bool,
+    /// Highest volume anyone can set, `/config volume` included.
+    pub max_volume: i16,
 }
 
 impl Default for GuildConfig {
@@ -32,6 +47,19 @@ impl Default for GuildConfig {
             allow_explicit: true,
             created_at: String::new(),
             updated_at: String::new(),
+            allow_http_urls: true,
+            announce_mode: "edit".to_string(),
+            announce_delete_previous: false,
+            announce_thread: false,
+            pause_when_alone: false,
+            alone_timeout: 600,
+            stay_channel_id: None,
+            fallback_playlist_id: None,
+            save_queue_on_disconnect: true,
+            voice_status: true,
+            fade_duration: 0,
+            crossfade: false,
+            max_volume: 200,
         }
     }
 }
@@ -66,3 +94,56 @@ pub struct PlaylistTrack {
     pub added_by: i64,
     pub added_at: String,
 }
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct GuildUrlRule {
+    pub id: i64,
+    pub guild_id: i64,
+    pub domain: String,
+    pub rule: String,
+    pub created_by: i64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct SavedQueue {
+    pub guild_id: i64,
+    /// JSON array of Lavalink tracks, the one that was playing first.
+    pub tracks: String,
+    pub saved_at: String,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct SleepTimer {
+    pub guild_id: i64,
+    /// `after`, `track` or `queue`.
+    pub mode: String,
+    /// Unix timestamp, only set for `after`.
+    pub ends_at: Option<i64>,
+    pub disconnect: bool,
+    /// Where to say that the timer went off.
+    pub channel_id: i64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ScheduledPlayback {
+    pub id: i64,
+    pub guild_id: i64,
+    pub playlist_id: i64,
+    pub voice_channel_id: i64,
+    /// Unix timestamp of the next start.
+    pub starts_at: i64,
+    /// `none`, `daily` or `weekly`.
+    pub repeat_every: String,
+    pub created_by: i64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct RoleVolumeLimit {
+    pub guild_id: i64,
+    pub role_id: i64,
+    pub max_volume: i16,
+    pub created_at: String,
+}
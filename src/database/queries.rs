@@ -97,6 +97,147 @@ pub async fn update_announce_settings(
     Ok(())
 }
 
+pub async fn update_announce_mode(
+    pool: &SqlitePool,
+    guild_id: i64,
+    announce_mode: &str,
+    delete_previous: bool,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE guild_configs 
+         SET announce_mode = ?, announce_delete_previous = ?, updated_at = CURRENT_TIMESTAMP 
+         WHERE guild_id = ?",
+    )
+    .bind(announce_mode)
+    .bind(delete_previous)
+    .bind(guild_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn update_announce_thread(
+    pool: &SqlitePool,
+    guild_id: i64,
+    announce_thread: bool,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE guild_configs 
+         SET announce_thread = ?, updated_at = CURRENT_TIMESTAMP 
+         WHERE guild_id = ?",
+    )
+    .bind(announce_thread)
+    .bind(guild_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn update_alone_settings(
+    pool: &SqlitePool,
+    guild_id: i64,
+    pause_when_alone: bool,
+    alone_timeout: i32,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE guild_configs 
+         SET pause_when_alone = ?, alone_timeout = ?, updated_at = CURRENT_TIMESTAMP 
+         WHERE guild_id = ?",
+    )
+    .bind(pause_when_alone)
+    .bind(alone_timeout)
+    .bind(guild_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn update_stay_settings(
+    pool: &SqlitePool,
+    guild_id: i64,
+    channel_id: Option<i64>,
+    playlist_id: Option<i64>,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE guild_configs 
+         SET stay_channel_id = ?, fallback_playlist_id = ?, updated_at = CURRENT_TIMESTAMP 
+         WHERE guild_id = ?",
+    )
+    .bind(channel_id)
+    .bind(playlist_id)
+    .bind(guild_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn update_fade_settings(
+    pool: &SqlitePool,
+    guild_id: i64,
+    fade_duration: i32,
+    crossfade: bool,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE guild_configs 
+         SET fade_duration = ?, crossfade = ?, updated_at = CURRENT_TIMESTAMP 
+         WHERE guild_id = ?",
+    )
+    .bind(fade_duration)
+    .bind(crossfade)
+    .bind(guild_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn update_max_volume(pool: &SqlitePool, guild_id: i64, max_volume: i16) -> Result<()> {
+    sqlx::query(
+        "UPDATE guild_configs 
+         SET max_volume = ?, volume = MIN(volume, ?), updated_at = CURRENT_TIMESTAMP 
+         WHERE guild_id = ?",
+    )
+    .bind(max_volume)
+    .bind(max_volume)
+    .bind(guild_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn update_voice_status_setting(
+    pool: &SqlitePool,
+    guild_id: i64,
+    enabled: bool,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE guild_configs 
+         SET voice_status = ?, updated_at = CURRENT_TIMESTAMP 
+         WHERE guild_id = ?",
+    )
+    .bind(enabled)
+    .bind(guild_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn update_save_queue_setting(
+    pool: &SqlitePool,
+    guild_id: i64,
+    enabled: bool,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE guild_configs 
+         SET save_queue_on_disconnect = ?, updated_at = CURRENT_TIMESTAMP 
+         WHERE guild_id = ?",
+    )
+    .bind(enabled)
+    .bind(guild_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 pub async fn update_max_queue_length(
     pool: &SqlitePool,
     guild_id: i64,
@@ -137,6 +278,16 @@ pub async fn reset_guild_config(pool: &SqlitePool, guild_id: i64) -> Result<()>
         .execute(pool)
         .await?;
 
+    sqlx::query("DELETE FROM guild_url_rules WHERE guild_id = ?")
+        .bind(guild_id)
+        .execute(pool)
+        .await?;
+
+    sqlx::query("DELETE FROM role_volume_limits WHERE guild_id = ?")
+        .bind(guild_id)
+        .execute(pool)
+        .await?;
+
     create_guild_config(pool, guild_id).await?;
     Ok(())
 }
@@ -201,6 +352,20 @@ pub async fn get_guild_playlists(pool: &SqlitePool, guild_id: i64) -> Result<Vec
     .await
 }
 
+pub async fn get_guild_playlist_by_name(
+    pool: &SqlitePool,
+    guild_id: i64,
+    name: &str,
+) -> Result<Option<GuildPlaylist>> {
+    sqlx::query_as::<_, GuildPlaylist>(
+        "SELECT * FROM guild_playlists WHERE guild_id = ? AND name = ? COLLATE NOCASE",
+    )
+    .bind(guild_id)
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+}
+
 pub async fn create_playlist(
     pool: &SqlitePool,
     guild_id: i64,
@@ -288,3 +453,253 @@ pub async fn remove_track_from_playlist(
         .await?;
     Ok(())
 }
+
+pub async fn update_http_urls_setting(
+    pool: &SqlitePool,
+    guild_id: i64,
+    allow_http_urls: bool,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE guild_configs 
+         SET allow_http_urls = ?, updated_at = CURRENT_TIMESTAMP 
+         WHERE guild_id = ?",
+    )
+    .bind(allow_http_urls)
+    .bind(guild_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_url_rules(pool: &SqlitePool, guild_id: i64) -> Result<Vec<GuildUrlRule>> {
+    sqlx::query_as::<_, GuildUrlRule>(
+        "SELECT * FROM guild_url_rules WHERE guild_id = ? ORDER BY rule, domain",
+    )
+    .bind(guild_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn set_url_rule(
+    pool: &SqlitePool,
+    guild_id: i64,
+    domain: &str,
+    rule: &str,
+    created_by: i64,
+) -> Result<GuildUrlRule> {
+    sqlx::query_as::<_, GuildUrlRule>(
+        "INSERT INTO guild_url_rules (guild_id, domain, rule, created_by)
+         VALUES (?, ?, ?, ?)
+         ON CONFLICT(guild_id, domain) DO UPDATE SET rule = excluded.rule, created_by = excluded.created_by
+         RETURNING *",
+    )
+    .bind(guild_id)
+    .bind(domain)
+    .bind(rule)
+    .bind(created_by)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn remove_url_rule(pool: &SqlitePool, guild_id: i64, domain: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM guild_url_rules WHERE guild_id = ? AND domain = ?")
+        .bind(guild_id)
+        .bind(domain)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_role_volume_limits(
+    pool: &SqlitePool,
+    guild_id: i64,
+) -> Result<Vec<RoleVolumeLimit>> {
+    sqlx::query_as::<_, RoleVolumeLimit>(
+        "SELECT * FROM role_volume_limits WHERE guild_id = ? ORDER BY max_volume",
+    )
+    .bind(guild_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn set_role_volume_limit(
+    pool: &SqlitePool,
+    guild_id: i64,
+    role_id: i64,
+    max_volume: i16,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO role_volume_limits (guild_id, role_id, max_volume)
+         VALUES (?, ?, ?)
+         ON CONFLICT(guild_id, role_id) DO UPDATE SET max_volume = excluded.max_volume",
+    )
+    .bind(guild_id)
+    .bind(role_id)
+    .bind(max_volume)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn remove_role_volume_limit(
+    pool: &SqlitePool,
+    guild_id: i64,
+    role_id: i64,
+) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM role_volume_limits WHERE guild_id = ? AND role_id = ?")
+        .bind(guild_id)
+        .bind(role_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn save_queue(pool: &SqlitePool, guild_id: i64, tracks: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO saved_queues (guild_id, tracks) VALUES (?, ?)
+         ON CONFLICT(guild_id) DO UPDATE SET tracks = excluded.tracks, saved_at = CURRENT_TIMESTAMP",
+    )
+    .bind(guild_id)
+    .bind(tracks)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Returns the guild's saved queue and removes it, so it is only restored once.
+pub async fn take_saved_queue(pool: &SqlitePool, guild_id: i64) -> Result<Option<SavedQueue>> {
+    sqlx::query_as::<_, SavedQueue>("DELETE FROM saved_queues WHERE guild_id = ? RETURNING *")
+        .bind(guild_id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn set_sleep_timer(
+    pool: &SqlitePool,
+    guild_id: i64,
+    mode: &str,
+    ends_at: Option<i64>,
+    disconnect: bool,
+    channel_id: i64,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO sleep_timers (guild_id, mode, ends_at, disconnect, channel_id) VALUES (?, ?, ?, ?, ?)
+         ON CONFLICT(guild_id) DO UPDATE SET mode = excluded.mode, ends_at = excluded.ends_at,
+         disconnect = excluded.disconnect, channel_id = excluded.channel_id, created_at = CURRENT_TIMESTAMP",
+    )
+    .bind(guild_id)
+    .bind(mode)
+    .bind(ends_at)
+    .bind(disconnect)
+    .bind(channel_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_sleep_timer(pool: &SqlitePool, guild_id: i64) -> Result<Option<SleepTimer>> {
+    sqlx::query_as::<_, SleepTimer>("SELECT * FROM sleep_timers WHERE guild_id = ?")
+        .bind(guild_id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn get_sleep_timers(pool: &SqlitePool) -> Result<Vec<SleepTimer>> {
+    sqlx::query_as::<_, SleepTimer>("SELECT * FROM sleep_timers")
+        .fetch_all(pool)
+        .await
+}
+
+/// Returns whether there was a timer to delete.
+pub async fn delete_sleep_timer(pool: &SqlitePool, guild_id: i64) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM sleep_timers WHERE guild_id = ?")
+        .bind(guild_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn create_schedule(
+    pool: &SqlitePool,
+    guild_id: i64,
+    playlist_id: i64,
+    voice_channel_id: i64,
+    starts_at: i64,
+    repeat_every: &str,
+    created_by: i64,
+) -> Result<ScheduledPlayback> {
+    sqlx::query_as::<_, ScheduledPlayback>(
+        "INSERT INTO scheduled_playbacks (guild_id, playlist_id, voice_channel_id, starts_at, repeat_every, created_by)
+         VALUES (?, ?, ?, ?, ?, ?)
+         RETURNING *",
+    )
+    .bind(guild_id)
+    .bind(playlist_id)
+    .bind(voice_channel_id)
+    .bind(starts_at)
+    .bind(repeat_every)
+    .bind(created_by)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn get_schedule(
+    pool: &SqlitePool,
+    schedule_id: i64,
+    guild_id: i64,
+) -> Result<Option<ScheduledPlayback>> {
+    sqlx::query_as::<_, ScheduledPlayback>(
+        "SELECT * FROM scheduled_playbacks WHERE id = ? AND guild_id = ?",
+    )
+    .bind(schedule_id)
+    .bind(guild_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn get_guild_schedules(
+    pool: &SqlitePool,
+    guild_id: i64,
+) -> Result<Vec<ScheduledPlayback>> {
+    sqlx::query_as::<_, ScheduledPlayback>(
+        "SELECT * FROM scheduled_playbacks WHERE guild_id = ? ORDER BY starts_at",
+    )
+    .bind(guild_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_due_schedules(pool: &SqlitePool, now: i64) -> Result<Vec<ScheduledPlayback>> {
+    sqlx::query_as::<_, ScheduledPlayback>(
+        "SELECT * FROM scheduled_playbacks WHERE starts_at <= ? ORDER BY starts_at",
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn update_schedule(pool: &SqlitePool, schedule: &ScheduledPlayback) -> Result<()> {
+    sqlx::query(
+        "UPDATE scheduled_playbacks 
+         SET playlist_id = ?, voice_channel_id = ?, starts_at = ?, repeat_every = ? 
+         WHERE id = ? AND guild_id = ?",
+    )
+    .bind(schedule.playlist_id)
+    .bind(schedule.voice_channel_id)
+    .bind(schedule.starts_at)
+    .bind(&schedule.repeat_every)
+    .bind(schedule.id)
+    .bind(schedule.guild_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn delete_schedule(pool: &SqlitePool, schedule_id: i64, guild_id: i64) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM scheduled_playbacks WHERE id = ? AND guild_id = ?")
+        .bind(schedule_id)
+        .bind(guild_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -9,24 +9,27 @@ pub mod websocket;
 
 use ::serenity::all::ActivityData;
 use database::Database;
-use std::{env, sync::Arc};
+use std::sync::Arc;
 use url::Url;
 
 use lavalink_rs::{
     client::LavalinkClient,
     model::{UserId, events},
-    node::NodeBuilder,
-    prelude::NodeDistributionStrategy,
 };
 use poise::serenity_prelude as serenity;
 use songbird::SerenityInit;
 
-use crate::{utils::constants::COLOR_INFO, websocket::server::ClientConnections};
+use crate::{
+    utils::{autodisconnect::AutoDisconnect, constants::COLOR_INFO, sleep::SleepTimers},
+    websocket::server::ClientConnections,
+};
 
 pub struct Data {
     pub lavalink: LavalinkClient,
     pub database: Database,
     pub ws_clients: Option<ClientConnections>,
+    pub auto_disconnect: AutoDisconnect,
+    pub sleep_timers: SleepTimers,
 }
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
@@ -84,46 +87,81 @@ fn set_guild_activity(ctx: &serenity::Context) {
     }));
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Error> {
-    dotenv::dotenv().ok();
+async fn event_handler(
+    ctx: &serenity::Context,
+    event: &serenity::FullEvent,
+    data: &Data,
+) -> Result<(), Error> {
+    match event {
+        serenity::FullEvent::InteractionCreate { interaction } => {
+            if let Some(component) = interaction.as_message_component() {
+                utils::player_message::handle_interaction(ctx, component, data).await?;
+            }
+        }
+        serenity::FullEvent::VoiceStateUpdate { old, new } => {
+            if let Some(guild_id) = new.guild_id {
+                data.auto_disconnect.notify(guild_id);
+            }
 
-    tracing_subscriber::fmt::init();
+            if new.user_id == ctx.cache.current_user().id {
+                utils::voicechannel::bot_voice_state_changed(ctx, data, old.as_ref(), new).await?;
+            }
+        }
+        serenity::FullEvent::GuildCreate { guild, .. } => {
+            if let Err(e) = utils::stay::rejoin(ctx, data, guild.id).await {
+                error!("Failed to join 24/7 channel in guild {}: {:?}", guild.id, e);
+            }
+        }
+        _ => {}
+    }
 
-    let database = Database::new("sqlite:data/onbeat.db")
-        .await
-        .expect("Failed to initialize database");
+    Ok(())
+}
 
+async fn init_lavalink(
+    user_id: UserId,
+    node_configs: Vec<utils::nodes::NodeConfig>,
+) -> LavalinkClient {
     let events = events::Events {
         raw: Some(music_events::raw_event),
+        ready: Some(music_events::ready_event),
+        player_update: Some(music_events::player_update),
         track_start: Some(music_events::track_start),
         track_end: Some(music_events::track_end),
         track_exception: Some(music_events::track_exception),
+        track_stuck: Some(music_events::track_stuck),
         websocket_closed: Some(music_events::websocket_closed),
         ..Default::default()
     };
 
-    let hostname = env::var("LAVA_HOST").expect("LAVA_HOST not set");
-    let password = env::var("LAVA_PASSWORD").expect("LAVA_PASSWORD not set");
-    let session_id = env::var("SESSION_ID").expect("SESSION_ID not set");
-
-    let node_local = NodeBuilder {
-        hostname,
-        is_ssl: false,
-        events: events::Events::default(),
-        password,
-        user_id: UserId(1459592284202078319), // BeatBot id
-        session_id: Some(session_id),
-    };
+    let nodes = node_configs
+        .iter()
+        .map(|config| config.builder(user_id))
+        .collect();
 
-    let lavalink = LavalinkClient::new(
+    let lavalink = LavalinkClient::new_with_data(
         events,
-        vec![node_local],
-        NodeDistributionStrategy::round_robin(),
+        nodes,
+        utils::nodes::strategy(),
+        Arc::new(utils::nodes::NodeRegistry::new(node_configs)),
     )
     .await;
+    utils::nodes::start_health_monitor(lavalink.clone());
 
-    let lavalink_clone = lavalink.clone();
+    lavalink
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    dotenv::dotenv().ok();
+
+    tracing_subscriber::fmt::init();
+
+    let database = Database::new("sqlite:data/onbeat.db")
+        .await
+        .expect("Failed to initialize database");
+
+    let node_configs = utils::nodes::NodeConfig::load();
     let ws_addr = std::env::var("WS_ADDR")
         .unwrap_or_else(|_| "127.0.0.1:4545".to_string())
         .parse()
@@ -133,46 +171,71 @@ async fn main() -> Result<(), Error> {
         .await
         .expect("Failed to initialize database for WebSocket");
     let ws_clients = websocket::server::ClientConnections::default();
-    let ws_clients_clone = ws_clients.clone();
-
-    let ws_data = Arc::new(Data {
-        lavalink: lavalink.clone(),
-        database: ws_database,
-        ws_clients: Some(ws_clients_clone),
-    });
-
-    let ws_server = websocket::server::WebSocketServer::new(ws_addr, ws_data);
 
     let framework = poise::Framework::<Data, Error>::builder()
         .options(poise::FrameworkOptions {
             commands: vec![
                 commands::join::join(),
                 commands::play::play(),
+                commands::play::play_attachment(),
                 commands::skip::skip(),
+                commands::sleep::sleep(),
                 commands::leave::leave(),
                 commands::queue::queue(),
+                commands::nowplaying::nowplaying(),
                 commands::volume::volume(),
                 commands::info::info(),
                 commands::config::config(),
                 commands::filters::filter(),
                 commands::seek::seek(),
+                commands::seek::forward(),
+                commands::seek::rewind(),
                 commands::clear::clear(),
                 commands::stop::stop(),
                 commands::pause::pause(),
                 commands::resume::resume(),
                 commands::remove::remove(),
+                commands::restore::restore(),
+                commands::schedule::schedule(),
             ],
+            event_handler: |ctx, event, _framework, data| Box::pin(event_handler(ctx, event, data)),
             ..Default::default()
         })
-        .setup(move |ctx, _ready, framework| {
-            let lavalink = lavalink_clone.clone();
+        .setup(move |ctx, ready, framework| {
             Box::pin(async move {
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
 
+                // Lavalink needs the bot's user id, which we only know once Discord has
+                // accepted the token, so the client is created here rather than up front.
+                let lavalink = init_lavalink(UserId(ready.user.id.get()), node_configs).await;
+                let auto_disconnect =
+                    AutoDisconnect::start(ctx.clone(), lavalink.clone(), database.pool().clone());
+                let sleep_timers =
+                    SleepTimers::start(ctx.clone(), lavalink.clone(), database.pool().clone());
+
+                let ws_data = Arc::new(Data {
+                    lavalink: lavalink.clone(),
+                    database: ws_database,
+                    ws_clients: Some(ws_clients.clone()),
+                    auto_disconnect: auto_disconnect.clone(),
+                    sleep_timers: sleep_timers.clone(),
+                });
+                utils::schedule::start(ctx.clone(), ws_data.clone());
+                let ws_server = websocket::server::WebSocketServer::new(ws_addr, ws_data);
+
+                tokio::spawn(async move {
+                    info!("Starting WebSocket server...");
+                    if let Err(e) = ws_server.start().await {
+                        error!("WebSocket server error: {}", e);
+                    }
+                });
+
                 Ok(Data {
                     lavalink,
                     database,
                     ws_clients: Some(ws_clients),
+                    auto_disconnect,
+                    sleep_timers,
                 })
             })
         })
@@ -187,12 +250,6 @@ async fn main() -> Result<(), Error> {
     .framework(framework)
     .await?;
 
-    tokio::spawn(async move {
-        info!("Starting WebSocket server...");
-        if let Err(e) = ws_server.start().await {
-            error!("WebSocket server error: {}", e);
-        }
-    });
     info!("Starting Discord bot...");
     client.start().await?;
 
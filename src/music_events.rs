@@ -1,8 +1,20 @@
-use lavalink_rs::{hook, model::events, prelude::*};
+use lavalink_rs::{
+    hook,
+    model::{events, track::TrackData},
+    prelude::*,
+};
 use poise::serenity_prelude as serenity;
+use std::time::Duration;
 
 use crate::{
-    utils::{announcements::AnnouncementBuilder, constants::COLOR_ERROR, player_data::PlayerData},
+    utils::{
+        announcements::{self, AnnounceMode, AnnouncementBuilder, display_track},
+        constants::COLOR_ERROR,
+        fade, nodes, playback,
+        player_data::PlayerData,
+        player_message::{self, PlayerStatus},
+        recovery, stage, stay, voice_status,
+    },
     websocket::server::{broadcast_player_event, broadcast_track_update},
 };
 
@@ -20,8 +32,31 @@ pub fn raw_event<'a>(
 
 #[hook]
 pub async fn ready_event(client: LavalinkClient, session_id: String, event: &events::Ready) {
-    client.delete_all_player_contexts().await.unwrap();
-    info!("{:?} -> {:?}", session_id, event);
+    info!(
+        "Lavalink session {} ready - Resumed: {}",
+        session_id, event.resumed
+    );
+
+    if let Err(e) = nodes::handle_ready(&client, &session_id, event.resumed).await {
+        error!("Failed to restore players after reconnecting: {:?}", e);
+    }
+}
+
+#[hook]
+pub async fn player_update(
+    client: LavalinkClient,
+    _session_id: String,
+    event: &events::PlayerUpdate,
+) {
+    if let Some(player) = client.get_player_context(event.guild_id)
+        && let Ok(data) = player.data::<PlayerData>()
+    {
+        if event.state.position > 0 {
+            recovery::playback_confirmed(&player, &data).await;
+        }
+        playback::refresh_up_next(&player, &data).await;
+        fade::position_updated(&player, &data, event.state.position).await;
+    }
 }
 
 #[hook]
@@ -31,13 +66,65 @@ pub async fn track_start(client: LavalinkClient, _session_id: String, event: &ev
     if let Some(player) = client.get_player_context(event.guild_id)
         && let Ok(data) = player.data::<PlayerData>()
     {
+        data.auto_disconnect.notify(event.guild_id.0.into());
+
+        let resumed = data
+            .state()
+            .resumed_track
+            .take_if(|encoded| *encoded == event.track.encoded)
+            .is_some();
+        if resumed {
+            info!("Resumed {:?} on another node", event.track.info.title);
+            return;
+        }
+
+        if data
+            .sleep_timers
+            .track_started(&player, &data, &event.track)
+            .await
+        {
+            return;
+        }
+
+        let track = display_track(&event.track);
+
+        let start_time = track
+            .user_data
+            .as_ref()
+            .and_then(|d| d["start_time"].as_u64())
+            .filter(|start| track.info.is_seekable && *start < track.info.length);
+
+        if let Some(start_time) = start_time
+            && let Err(e) = player.set_position(Duration::from_millis(start_time)).await
+        {
+            error!("Failed to seek to requested start time: {:?}", e);
+        }
+
+        let playable_ms = if track.info.is_stream {
+            u64::MAX
+        } else {
+            track.info.length - start_time.unwrap_or_default()
+        };
+
+        let loop_mode = {
+            let mut state = data.state();
+            state.playback_confirmed = false;
+            state
+                .session
+                .track_started(player_message::requester_id(&track), playable_ms);
+            state.loop_mode
+        };
+        if let Err(e) = playback::queue_loop_copy(&player, loop_mode, &event.track) {
+            error!("Failed to queue looped track: {:?}", e);
+        }
+
         let track_info = serde_json::json!({
-            "title": event.track.info.title,
+            "title": track.info.title,
             "author": event.track.info.author,
             "uri": event.track.info.uri,
             "artwork_url": event.track.info.artwork_url,
             "length": event.track.info.length,
-            "position": 0,
+            "position": start_time.unwrap_or_default(),
             "requester_id": event.track.user_data.as_ref()
                 .and_then(|d| d["requester_id"].as_u64())
                 .map(|id| id.to_string())
@@ -48,6 +135,14 @@ pub async fn track_start(client: LavalinkClient, _session_id: String, event: &ev
             let _ = broadcast_track_update(ws_clients, event.guild_id.0, track_info).await;
         }
 
+        let stage_channel = {
+            let state = data.state();
+            state.voice_channel.filter(|_| state.on_stage)
+        };
+        if let Some(channel_id) = stage_channel {
+            stage::set_topic(&data.http, channel_id, &stage::topic(&track)).await;
+        }
+
         let config =
             match crate::database::queries::get_guild_config(&data.db, event.guild_id.0 as i64)
                 .await
@@ -59,33 +154,70 @@ pub async fn track_start(client: LavalinkClient, _session_id: String, event: &ev
                 }
             };
 
-        if !config.announce_songs {
+        fade::configure(&data, &config);
+        if let Err(e) = fade::track_started(&player, &data).await {
+            error!("Failed to fade in: {:?}", e);
+        }
+
+        if let Err(e) = stay::queue_fallback(&player, &data, &config).await {
+            error!("Failed to queue fallback track: {:?}", e);
+        }
+        playback::refresh_up_next(&player, &data).await;
+
+        if config.voice_status {
+            voice_status::set(&data, voice_status::status(&track));
+        }
+
+        let mode = AnnounceMode::from_config(&config);
+        if mode == AnnounceMode::Off {
             return;
         }
 
-        let announce_channel = if let Some(channel_id) = config.announce_channel_id {
-            serenity::ChannelId::new(channel_id as u64)
-        } else {
-            data.channel_id
+        let announce_channel = announcements::announce_channel(&data, &config);
+
+        if config.announce_delete_previous {
+            announcements::delete_previous(&data).await;
+        }
+
+        if mode == AnnounceMode::Compact {
+            let embed = AnnouncementBuilder::compact(&track, player_message::requester_id(&track));
+
+            match announce_channel
+                .send_message(
+                    data.http.as_ref(),
+                    serenity::CreateMessage::default().embed(embed),
+                )
+                .await
+            {
+                Ok(message) => {
+                    announcements::remember(&data, &config, announce_channel, message.id)
+                }
+                Err(e) => error!("Failed to send track start announcement: {:?}", e),
+            }
+            return;
+        }
+
+        let status = match PlayerStatus::fetch(&player, &data).await {
+            Ok((_, status)) => PlayerStatus {
+                position: start_time.unwrap_or_default(),
+                ..status
+            },
+            Err(e) => {
+                error!("Failed to get player status: {:?}", e);
+                return;
+            }
         };
 
-        let requester_id = event
-            .track
-            .user_data
-            .as_ref()
-            .and_then(|d| d["requester_id"].as_u64())
-            .unwrap_or_default();
-        let embed = AnnouncementBuilder::now_playing(&event.track, requester_id);
-
-        if let Err(e) = announce_channel
-            .send_message(
-                data.http.as_ref(),
-                serenity::CreateMessage::default().embed(embed),
-            )
-            .await
-        {
-            error!("Failed to send track start announcement: {:?}", e);
+        let edit_existing = mode == AnnounceMode::Edit;
+        match player_message::show(&data, announce_channel, &track, &status, edit_existing).await {
+            Ok(message_id) if !edit_existing => {
+                announcements::remember(&data, &config, announce_channel, message_id);
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to send track start announcement: {:?}", e),
         }
+
+        player_message::start_updater(client.clone(), event.guild_id, data);
     }
 }
 
@@ -99,9 +231,18 @@ pub async fn track_end(client: LavalinkClient, _session_id: String, event: &even
     if let Some(player) = client.get_player_context(event.guild_id)
         && let Ok(data) = player.data::<PlayerData>()
     {
+        data.auto_disconnect.notify(event.guild_id.0.into());
+
+        data.state().session.track_ended();
+
+        if event.reason == events::TrackEndReason::Finished {
+            recovery::track_finished(&player, &data, &event.track).await;
+        }
+
+        let track = display_track(&event.track);
         let event_data = serde_json::json!({
-            "title": event.track.info.title,
-            "author": event.track.info.author,
+            "title": track.info.title,
+            "author": track.info.author,
             "reason": format!("{:?}", event.reason),
         });
 
@@ -110,6 +251,22 @@ pub async fn track_end(client: LavalinkClient, _session_id: String, event: &even
                 broadcast_player_event(ws_clients, event.guild_id.0, "trackEnd", event_data).await;
         }
 
+        // Only these move on to the next track, and the player's own view of whether there
+        // is one isn't up to date yet.
+        let advances = matches!(
+            event.reason,
+            events::TrackEndReason::Finished | events::TrackEndReason::LoadFailed
+        );
+        if event.reason != events::TrackEndReason::Replaced
+            && (!advances || !data.state().up_next.anything())
+        {
+            voice_status::clear(&data);
+        }
+
+        data.sleep_timers
+            .track_ended(&player, &data, &event.reason)
+            .await;
+
         if event.reason != events::TrackEndReason::Replaced
             && event.reason != events::TrackEndReason::Stopped
         {
@@ -121,24 +278,23 @@ pub async fn track_end(client: LavalinkClient, _session_id: String, event: &even
                     Err(_) => return,
                 };
 
-            if !config.announce_songs {
+            if AnnounceMode::from_config(&config) != AnnounceMode::Full {
                 return;
             }
 
-            let announce_channel = if let Some(channel_id) = config.announce_channel_id {
-                serenity::ChannelId::new(channel_id as u64)
-            } else {
-                data.channel_id
-            };
+            let announce_channel = announcements::announce_channel(&data, &config);
 
-            let embed = AnnouncementBuilder::track_ended(&event.track);
+            let embed = AnnouncementBuilder::track_ended(&track);
 
-            let _ = announce_channel
+            if let Ok(message) = announce_channel
                 .send_message(
                     data.http.as_ref(),
                     serenity::CreateMessage::default().embed(embed),
                 )
-                .await;
+                .await
+            {
+                announcements::remember(&data, &config, announce_channel, message.id);
+            }
         }
     }
 }
@@ -157,9 +313,10 @@ pub async fn track_exception(
     if let Some(player) = client.get_player_context(event.guild_id)
         && let Ok(data) = player.data::<PlayerData>()
     {
+        let track = display_track(&event.track);
         let error_data = serde_json::json!({
-            "title": event.track.info.title,
-            "author": event.track.info.author,
+            "title": track.info.title,
+            "author": track.info.author,
             "error": event.exception.message,
             "severity": format!("{:?}", event.exception.severity),
         });
@@ -170,24 +327,82 @@ pub async fn track_exception(
                     .await;
         }
 
-        let embed = serenity::CreateEmbed::default()
-            .title("<:forbidden2:1459603724895780970> Playback Error")
-            .description(format!(
-                "Failed to play **{} - {}**\n\nError: {}",
-                event.track.info.author, event.track.info.title, event.exception.message
-            ))
-            .color(COLOR_ERROR);
+        report_failure(
+            &player,
+            &data,
+            &event.track,
+            &event.exception.message,
+            false,
+        )
+        .await;
+    }
+}
 
-        let _ = data
-            .channel_id
-            .send_message(
-                data.http.as_ref(),
-                serenity::CreateMessage::default().embed(embed),
-            )
-            .await;
+#[hook]
+pub async fn track_stuck(client: LavalinkClient, _session_id: String, event: &events::TrackStuck) {
+    warn!(
+        "Track stuck: {:?} - Threshold: {}ms",
+        event.track.info.title, event.threshold_ms
+    );
+
+    if let Some(player) = client.get_player_context(event.guild_id)
+        && let Ok(data) = player.data::<PlayerData>()
+    {
+        let track = display_track(&event.track);
+        let stuck_data = serde_json::json!({
+            "title": track.info.title,
+            "author": track.info.author,
+            "threshold_ms": event.threshold_ms,
+        });
+
+        if let Some(ws_clients) = &data.ws_clients {
+            let _ = broadcast_player_event(ws_clients, event.guild_id.0, "trackStuck", stuck_data)
+                .await;
+        }
+
+        let error = format!("No audio for {}s", event.threshold_ms / 1000);
+        report_failure(&player, &data, &event.track, &error, true).await;
     }
 }
 
+async fn report_failure(
+    player: &PlayerContext,
+    data: &PlayerData,
+    failed: &TrackData,
+    error: &str,
+    stuck: bool,
+) {
+    let outcome = match recovery::recover(player, data, failed, stuck).await {
+        Ok(recovery) => recovery.describe(),
+        Err(e) => {
+            error!("Failed to recover from playback error: {:?}", e);
+
+            // A failed track has already been followed by the next one.
+            if stuck && let Err(e) = playback::skip(player, data).await {
+                error!("Failed to skip the stuck track: {:?}", e);
+            }
+            "Could not recover, skipping.".to_string()
+        }
+    };
+
+    let track = display_track(failed);
+    let embed = serenity::CreateEmbed::default()
+        .title("<:forbidden2:1459603724895780970> Playback Error")
+        .description(format!(
+            "Failed to play **{} - {}**\n\nError: {}\n{}",
+            track.info.author, track.info.title, error, outcome
+        ))
+        .color(COLOR_ERROR);
+
+    let _ = data
+        .channel_id
+        .send_message(
+            data.http.as_ref(),
+            serenity::CreateMessage::default().embed(embed),
+        )
+        .await;
+}
+
 #[hook]
 pub async fn websocket_closed(
     client: LavalinkClient,
@@ -1,27 +1,113 @@
-use crate::{database::queries, utils::constants::COLOR_INFO};
+use crate::{
+    database::{models::GuildConfig, queries},
+    utils::{
+        constants::COLOR_INFO,
+        player_data::PlayerData,
+        player_message::{PlayerStatus, progress_bar},
+        session::{SessionStats, TOP_REQUESTERS},
+        time::format_duration,
+    },
+};
 use lavalink_rs::model::track::TrackData;
 use poise::serenity_prelude as serenity;
 
 pub struct AnnouncementBuilder;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum AnnounceMode {
+    Off,
+    Compact,
+    Full,
+    #[name = "Edit in place"]
+    Edit,
+}
+
+impl AnnounceMode {
+    pub const fn as_str(&self) -> &str {
+        match self {
+            Self::Off => "off",
+            Self::Compact => "compact",
+            Self::Full => "full",
+            Self::Edit => "edit",
+        }
+    }
+
+    pub fn from_config(config: &GuildConfig) -> Self {
+        if !config.announce_songs {
+            return Self::Off;
+        }
+
+        match config.announce_mode.as_str() {
+            "off" => Self::Off,
+            "compact" => Self::Compact,
+            "full" => Self::Full,
+            _ => Self::Edit,
+        }
+    }
+}
+
+/// Uploaded files go through Lavalink's HTTP source, which knows nothing but the URL,
+/// so the title picked when queueing travels in `user_data`.
+pub fn display_track(track: &TrackData) -> TrackData {
+    let mut track = track.clone();
+
+    if let Some(title) = track.user_data.as_ref().and_then(|d| d["title"].as_str()) {
+        track.info.title = title.to_string();
+    }
+
+    track
+}
+
+/// "Title - Author" for places with a length limit, like a stage topic.
+pub fn track_line(track: &TrackData, max_len: usize) -> String {
+    let track = display_track(track);
+    let line = format!("{} - {}", track.info.title, track.info.author);
+
+    if line.chars().count() > max_len {
+        let mut line: String = line.chars().take(max_len - 3).collect();
+        line.push_str("...");
+        line
+    } else {
+        line
+    }
+}
+
 impl AnnouncementBuilder {
-    pub fn now_playing(track: &TrackData, requester_id: u64) -> serenity::CreateEmbed {
-        let duration = if track.info.length > 0 {
-            format_duration(track.info.length)
-        } else {
+    pub fn now_playing(
+        track: &TrackData,
+        requester_id: u64,
+        status: &PlayerStatus,
+    ) -> serenity::CreateEmbed {
+        let progress = if track.info.is_stream {
             "<:star:1460009999513161914> LIVE".to_string()
+        } else {
+            format!(
+                "{} `{} / {}`",
+                progress_bar(status.position, track.info.length),
+                format_duration(status.position),
+                format_duration(track.info.length)
+            )
+        };
+
+        let title = if status.paused {
+            "<:disc:1459594790248251610> Paused"
+        } else {
+            "<:disc:1459594790248251610> Now Playing"
         };
 
         let mut embed = serenity::CreateEmbed::default()
-            .title("<:disc:1459594790248251610> Now Playing")
+            .title(title)
             .description(format!(
-                "**[{} - {}]({})**",
+                "**[{} - {}]({})**\n\n{progress}",
                 track.info.author,
                 track.info.title,
                 track.info.uri.as_deref().unwrap_or("#")
             ))
-            .field("Duration", duration, true)
             .field("Requested by", format!("<@{requester_id}>"), true)
+            .field("Loop", status.loop_mode.name(), true)
+            .field("Volume", format!("{}%", status.volume), true)
+            .field("Filter", status.filter.as_deref().unwrap_or("None"), true)
+            .field("Up Next", format!("{} tracks", status.queue_len), true)
             .color(COLOR_INFO)
             .timestamp(serenity::Timestamp::now());
 
@@ -32,6 +118,49 @@ impl AnnouncementBuilder {
         embed
     }
 
+    pub fn compact(track: &TrackData, requester_id: u64) -> serenity::CreateEmbed {
+        let duration = if track.info.is_stream {
+            "LIVE".to_string()
+        } else {
+            format_duration(track.info.length)
+        };
+
+        serenity::CreateEmbed::default()
+            .description(format!(
+                "<:disc:1459594790248251610> **[{} - {}]({})** `{duration}` • <@{requester_id}>",
+                track.info.author,
+                track.info.title,
+                track.info.uri.as_deref().unwrap_or("#")
+            ))
+            .color(COLOR_INFO)
+    }
+
+    pub fn session_summary(stats: &SessionStats) -> serenity::CreateEmbed {
+        let top_requesters = stats
+            .top_requesters(TOP_REQUESTERS)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (id, tracks))| format!("{}. <@{id}> - {tracks} tracks", i + 1))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let top_requesters = if top_requesters.is_empty() {
+            "Nobody".to_string()
+        } else {
+            top_requesters
+        };
+
+        serenity::CreateEmbed::default()
+            .title("<:disc:1459594790248251610> Session Ended")
+            .field("Duration", format_duration(stats.duration_ms()), true)
+            .field("Listening Time", format_duration(stats.listened_ms), true)
+            .field("Tracks Played", stats.tracks_played.to_string(), true)
+            .field("Skipped", stats.skipped.to_string(), true)
+            .field("Top Requesters", top_requesters, false)
+            .color(COLOR_INFO)
+            .timestamp(serenity::Timestamp::now())
+    }
+
     pub fn added_to_queue(
         track: &TrackData,
         requester_id: u64,
@@ -87,15 +216,50 @@ impl AnnouncementBuilder {
     }
 }
 
-fn format_duration(ms: u64) -> String {
-    let seconds = ms / 1000;
-    let minutes = seconds / 60;
-    let hours = minutes / 60;
+/// The session thread if there is one, otherwise the configured announcement channel,
+/// otherwise the channel the player was started from.
+pub fn announce_channel(data: &PlayerData, config: &GuildConfig) -> serenity::ChannelId {
+    if let Some(thread) = data.state().session_thread {
+        return thread;
+    }
 
-    if hours > 0 {
-        format!("{hours}:{:02}:{:02}", minutes % 60, seconds % 60)
-    } else {
-        format!("{minutes}:{:02}", seconds % 60)
+    config
+        .announce_channel_id
+        .map_or(data.channel_id, |id| serenity::ChannelId::new(id as u64))
+}
+
+/// Remembers a posted announcement so it can be cleaned up when the next track starts.
+/// Does nothing unless the guild has that cleanup turned on, as nothing would drain it.
+pub fn remember(
+    data: &PlayerData,
+    config: &GuildConfig,
+    channel_id: serenity::ChannelId,
+    message_id: serenity::MessageId,
+) {
+    if config.announce_delete_previous {
+        data.state().announcements.push((channel_id, message_id));
+    }
+}
+
+pub async fn delete_previous(data: &PlayerData) {
+    let messages = {
+        let mut state = data.state();
+        let messages = std::mem::take(&mut state.announcements);
+
+        if state
+            .player_message
+            .is_some_and(|message| messages.contains(&message))
+        {
+            state.player_message = None;
+        }
+
+        messages
+    };
+
+    for (channel_id, message_id) in messages {
+        if let Err(e) = channel_id.delete_message(&data.http, message_id).await {
+            warn!("Failed to delete previous announcement: {:?}", e);
+        }
     }
 }
 
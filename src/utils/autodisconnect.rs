@@ -1,152 +1,394 @@
+use lavalink_rs::prelude::{LavalinkClient, PlayerContext};
 use poise::serenity_prelude as serenity;
-use std::time::Duration;
-use tokio::time::sleep;
+use std::{collections::HashMap, time::Duration};
+use tokio::{
+    sync::mpsc,
+    time::{Instant, sleep_until},
+};
 
-use crate::database::queries;
+use crate::{
+    database::{models::GuildConfig, queries},
+    utils::{player_data::PlayerData, voicechannel::_leave},
+};
 
-pub struct AutoDisconnectManager {
-    guild_id: serenity::GuildId,
-    db: sqlx::SqlitePool,
-    ctx: serenity::Context,
+/// The pending disconnect of every guild. Holds no I/O and takes the current time as an
+/// argument, so it can be driven by a fake clock.
+#[derive(Debug, Default)]
+pub struct DisconnectTimers {
+    deadlines: HashMap<serenity::GuildId, Instant>,
 }
 
-impl AutoDisconnectManager {
-    pub const fn new(
-        guild_id: serenity::GuildId,
-        db: sqlx::SqlitePool,
+impl DisconnectTimers {
+    /// Starts the countdown for a guild and returns true, or returns false if one is
+    /// already running. A running countdown is only ever brought forward, so repeated
+    /// events don't keep pushing the deadline back.
+    pub fn schedule(&mut self, guild_id: serenity::GuildId, now: Instant, after: Duration) -> bool {
+        let deadline = now + after;
+
+        match self.deadlines.get_mut(&guild_id) {
+            Some(current) => {
+                *current = (*current).min(deadline);
+                false
+            }
+            None => {
+                self.deadlines.insert(guild_id, deadline);
+                true
+            }
+        }
+    }
+
+    pub fn cancel(&mut self, guild_id: serenity::GuildId) -> bool {
+        self.deadlines.remove(&guild_id).is_some()
+    }
+
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.deadlines.values().min().copied()
+    }
+
+    /// Removes and returns the guilds whose countdown has run out.
+    pub fn take_due(&mut self, now: Instant) -> Vec<serenity::GuildId> {
+        let due: Vec<_> = self
+            .deadlines
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(guild_id, _)| *guild_id)
+            .collect();
+
+        for guild_id in &due {
+            self.deadlines.remove(guild_id);
+        }
+
+        due
+    }
+}
+
+/// Handle to the auto-disconnect scheduler. Call `notify` whenever something that
+/// affects whether the bot is in use changes in a guild: a track starting or ending,
+/// or someone joining or leaving voice.
+#[derive(Clone)]
+pub struct AutoDisconnect {
+    tx: mpsc::UnboundedSender<serenity::GuildId>,
+}
+
+impl AutoDisconnect {
+    pub fn start(
         ctx: serenity::Context,
+        lava_client: LavalinkClient,
+        db: sqlx::SqlitePool,
     ) -> Self {
-        Self { guild_id, db, ctx }
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let scheduler = Scheduler {
+            ctx,
+            lava_client,
+            db,
+            timers: DisconnectTimers::default(),
+        };
+        tokio::spawn(scheduler.run(rx));
+
+        Self { tx }
+    }
+
+    pub fn notify(&self, guild_id: serenity::GuildId) {
+        let _ = self.tx.send(guild_id);
     }
+}
 
-    pub async fn start_monitoring(self, lava_client: lavalink_rs::client::LavalinkClient) {
-        tokio::spawn(async move {
-            loop {
-                sleep(Duration::from_secs(10)).await;
+struct Scheduler {
+    ctx: serenity::Context,
+    lava_client: LavalinkClient,
+    db: sqlx::SqlitePool,
+    timers: DisconnectTimers,
+}
 
-                let config =
-                    match queries::get_guild_config(&self.db, self.guild_id.get() as i64).await {
-                        Ok(c) => c,
-                        Err(e) => {
-                            error!("Failed to get guild config for auto disconnect: {:?}", e);
-                            break;
-                        }
-                    };
+impl Scheduler {
+    async fn run(mut self, mut rx: mpsc::UnboundedReceiver<serenity::GuildId>) {
+        loop {
+            let deadline = self.timers.next_deadline();
 
-                if !config.auto_disconnect {
-                    continue;
+            tokio::select! {
+                guild_id = rx.recv() => {
+                    let Some(guild_id) = guild_id else {
+                        break;
+                    };
+                    self.update(guild_id).await;
+                }
+                () = sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                    for guild_id in self.timers.take_due(Instant::now()) {
+                        self.expire(guild_id).await;
+                    }
                 }
+            }
+        }
+    }
+
+    async fn status(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Option<(GuildConfig, PlayerContext, Status)> {
+        let config = match queries::get_guild_config(&self.db, guild_id.get() as i64).await {
+            Ok(c) => c,
+            Err(e) => {
+                error!("Failed to get guild config for auto disconnect: {:?}", e);
+                return None;
+            }
+        };
 
-                let Some(player) = lava_client.get_player_context(self.guild_id) else {
-                    break;
-                };
+        let player = self.lava_client.get_player_context(guild_id)?;
+        let player_data = player.get_player().await.ok()?;
+        let queue_count = player.get_queue().get_count().await.ok()?;
 
-                let player_data = match player.get_player().await {
-                    Ok(data) => data,
-                    Err(_) => break,
-                };
+        let status = Status {
+            alone: is_alone(&self.ctx.cache, guild_id),
+            idle: player_data.track.is_none() && queue_count == 0,
+            playing: player_data.track.is_some() && !player_data.paused,
+        };
 
-                if player_data.track.is_none() {
-                    let queue_count = match player.get_queue().get_count().await {
-                        Ok(count) => count,
-                        Err(_) => break,
-                    };
+        Some((config, player, status))
+    }
 
-                    if queue_count == 0 {
-                        let is_alone = self.check_if_alone().await;
-
-                        if is_alone {
-                            info!(
-                                "Auto-disconnect: Starting countdown for guild {}",
-                                self.guild_id
-                            );
-
-                            let disconnect_time = config.auto_disconnect_time as u64;
-                            sleep(Duration::from_secs(disconnect_time)).await;
-
-                            let config = match queries::get_guild_config(
-                                &self.db,
-                                self.guild_id.get() as i64,
-                            )
-                            .await
-                            {
-                                Ok(c) => c,
-                                Err(_) => break,
-                            };
-
-                            if !config.auto_disconnect {
-                                continue;
-                            }
-
-                            let Some(player) = lava_client.get_player_context(self.guild_id) else {
-                                break;
-                            };
-
-                            let player_data = match player.get_player().await {
-                                Ok(data) => data,
-                                Err(_) => break,
-                            };
-
-                            let queue_count = match player.get_queue().get_count().await {
-                                Ok(count) => count,
-                                Err(_) => break,
-                            };
-
-                            if player_data.track.is_none() && queue_count == 0 {
-                                info!(
-                                    "Auto-disconnect: Disconnecting from guild {} after {disconnect_time} seconds of inactivity",
-                                    self.guild_id
-                                );
-
-                                let manager = match songbird::get(&self.ctx).await {
-                                    Some(m) => m,
-                                    None => break,
-                                };
-
-                                let _ = lava_client.delete_player(self.guild_id).await;
-
-                                if manager.get(self.guild_id).is_some() {
-                                    let _ = manager.remove(self.guild_id).await;
-                                }
-
-                                break;
-                            }
-                        }
-                    }
+    async fn update(&mut self, guild_id: serenity::GuildId) {
+        let timeout = match self.status(guild_id).await {
+            Some((config, player, status)) => {
+                pause_or_resume(&config, &player, &status).await;
+                status.disconnect_after(&config)
+            }
+            None => None,
+        };
+
+        match timeout {
+            Some(timeout) => {
+                if self.timers.schedule(guild_id, Instant::now(), timeout) {
+                    info!("Auto-disconnect: Starting countdown for guild {}", guild_id);
+                }
+            }
+            None => {
+                if self.timers.cancel(guild_id) {
+                    info!(
+                        "Auto-disconnect: Cancelled countdown for guild {}",
+                        guild_id
+                    );
                 }
             }
-        });
+        }
     }
 
-    async fn check_if_alone(&self) -> bool {
-        let cache = self.ctx.cache.clone(); /*match self.ctx.cache() {
-        Some(c) => c,
-        None => return false,
-        };*/
+    async fn expire(&mut self, guild_id: serenity::GuildId) {
+        // Not everything that matters sends an event, a config change for one, so check
+        // again before leaving.
+        let Some((config, _, status)) = self.status(guild_id).await else {
+            return;
+        };
+        let Some(timeout) = status.disconnect_after(&config) else {
+            return;
+        };
+
+        info!(
+            "Auto-disconnect: Disconnecting from guild {} after {} seconds {}",
+            guild_id,
+            timeout.as_secs(),
+            if status.idle {
+                "of inactivity"
+            } else {
+                "alone"
+            }
+        );
 
-        let guild = match cache.guild(self.guild_id) {
-            Some(g) => g,
-            None => return false,
+        let Some(manager) = songbird::get(&self.ctx).await else {
+            return;
         };
 
-        let bot_id = cache.current_user().id;
+        if let Err(e) = _leave(&self.lava_client, &manager, guild_id).await {
+            error!("Auto-disconnect failed: {:?}", e);
+        }
+    }
+}
 
-        let bot_voice_channel = guild.voice_states.get(&bot_id).and_then(|vs| vs.channel_id);
+#[derive(Debug, Clone, Copy)]
+pub struct Status {
+    /// No listeners other than bots are in the bot's channel.
+    pub alone: bool,
+    /// Nothing is playing and nothing is queued.
+    pub idle: bool,
+    pub playing: bool,
+}
 
-        if let Some(channel_id) = bot_voice_channel {
-            let members_in_channel: Vec<_> = guild
-                .voice_states
-                .iter()
-                .filter(|(_, vs)| vs.channel_id == Some(channel_id))
-                .filter(|(user_id, _)| **user_id != bot_id)
-                .collect();
+impl Status {
+    /// How long to wait before leaving the guild, or `None` if the bot should stay.
+    pub fn disconnect_after(&self, config: &GuildConfig) -> Option<Duration> {
+        if !config.auto_disconnect || config.stay_channel_id.is_some() || !self.alone {
+            return None;
+        }
 
-            members_in_channel.is_empty()
-                || members_in_channel
-                    .iter()
-                    .all(|(user_id, _)| cache.user(**user_id).map(|u| u.bot).unwrap_or_default())
+        let secs = if self.idle {
+            config.auto_disconnect_time
         } else {
-            false
+            config.alone_timeout
+        };
+
+        Some(Duration::from_secs(secs.max(0) as u64))
+    }
+}
+
+/// Pauses as soon as the last listener leaves, and resumes when someone comes back if
+/// we were the ones who paused.
+async fn pause_or_resume(config: &GuildConfig, player: &PlayerContext, status: &Status) {
+    let Ok(data) = player.data::<PlayerData>() else {
+        return;
+    };
+
+    let pause = if status.alone {
+        if !config.pause_when_alone || !status.playing {
+            return;
+        }
+        data.state().paused_when_alone = true;
+        true
+    } else {
+        if !std::mem::take(&mut data.state().paused_when_alone) {
+            return;
+        }
+        false
+    };
+
+    if let Err(e) = player.set_pause(pause).await {
+        error!(
+            "Failed to pause or resume for guild {}: {:?}",
+            player.guild_id.0, e
+        );
+    }
+}
+
+/// Whether the bot's voice channel has no listeners other than bots.
+pub fn is_alone(cache: &serenity::Cache, guild_id: serenity::GuildId) -> bool {
+    let Some(guild) = cache.guild(guild_id) else {
+        return false;
+    };
+
+    let bot_id = cache.current_user().id;
+
+    let Some(channel_id) = guild.voice_states.get(&bot_id).and_then(|vs| vs.channel_id) else {
+        return false;
+    };
+
+    guild
+        .voice_states
+        .iter()
+        .filter(|(_, vs)| vs.channel_id == Some(channel_id))
+        .filter(|(user_id, _)| **user_id != bot_id)
+        .all(|(user_id, _)| cache.user(*user_id).map(|u| u.bot).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guild(id: u64) -> serenity::GuildId {
+        serenity::GuildId::new(id)
+    }
+
+    const fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn first_schedule_starts_a_countdown() {
+        let mut timers = DisconnectTimers::default();
+        let now = Instant::now();
+
+        assert!(timers.schedule(guild(1), now, secs(30)));
+        assert_eq!(timers.next_deadline(), Some(now + secs(30)));
+    }
+
+    #[test]
+    fn repeat_schedule_only_brings_the_deadline_forward() {
+        let mut timers = DisconnectTimers::default();
+        let now = Instant::now();
+
+        timers.schedule(guild(1), now, secs(30));
+
+        assert!(!timers.schedule(guild(1), now + secs(10), secs(60)));
+        assert_eq!(timers.next_deadline(), Some(now + secs(30)));
+
+        assert!(!timers.schedule(guild(1), now + secs(10), secs(5)));
+        assert_eq!(timers.next_deadline(), Some(now + secs(15)));
+    }
+
+    #[test]
+    fn cancel_reports_whether_there_was_a_timer() {
+        let mut timers = DisconnectTimers::default();
+        let now = Instant::now();
+
+        assert!(!timers.cancel(guild(1)));
+
+        timers.schedule(guild(1), now, secs(30));
+        assert!(timers.cancel(guild(1)));
+        assert!(!timers.cancel(guild(1)));
+        assert_eq!(timers.next_deadline(), None);
+    }
+
+    #[test]
+    fn take_due_removes_only_expired_guilds() {
+        let mut timers = DisconnectTimers::default();
+        let now = Instant::now();
+
+        timers.schedule(guild(1), now, secs(10));
+        timers.schedule(guild(2), now, secs(20));
+        timers.schedule(guild(3), now, secs(30));
+
+        let mut due = timers.take_due(now + secs(20));
+        due.sort();
+        assert_eq!(due, vec![guild(1), guild(2)]);
+
+        assert_eq!(timers.next_deadline(), Some(now + secs(30)));
+        assert!(timers.take_due(now + secs(20)).is_empty());
+        assert_eq!(timers.take_due(now + secs(30)), vec![guild(3)]);
+    }
+
+    fn config() -> GuildConfig {
+        GuildConfig {
+            auto_disconnect: true,
+            auto_disconnect_time: 300,
+            alone_timeout: 600,
+            ..Default::default()
         }
     }
+
+    const ALONE_IDLE: Status = Status {
+        alone: true,
+        idle: true,
+        playing: false,
+    };
+
+    const ALONE_PLAYING: Status = Status {
+        alone: true,
+        idle: false,
+        playing: true,
+    };
+
+    #[test]
+    fn idle_and_alone_use_their_own_timeouts() {
+        assert_eq!(ALONE_IDLE.disconnect_after(&config()), Some(secs(300)));
+        assert_eq!(ALONE_PLAYING.disconnect_after(&config()), Some(secs(600)));
+    }
+
+    #[test]
+    fn stays_when_there_is_no_reason_to_leave() {
+        let stay = GuildConfig {
+            stay_channel_id: Some(1),
+            ..config()
+        };
+        assert_eq!(ALONE_IDLE.disconnect_after(&stay), None);
+
+        let disabled = GuildConfig {
+            auto_disconnect: false,
+            ..config()
+        };
+        assert_eq!(ALONE_IDLE.disconnect_after(&disabled), None);
+
+        let listened_to = Status {
+            alone: false,
+            ..ALONE_IDLE
+        };
+        assert_eq!(listened_to.disconnect_after(&config()), None);
+    }
 }
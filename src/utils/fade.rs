@@ -0,0 +1,175 @@
+use lavalink_rs::prelude::{LavalinkResult, PlayerContext};
+use std::time::Duration;
+
+use crate::{database::models::GuildConfig, utils::player_data::PlayerData};
+
+const STEP: Duration = Duration::from_millis(250);
+/// Lavalink sends a position update every few seconds, so the end-of-track fade has to
+/// be planned this far ahead.
+const UPDATE_INTERVAL: Duration = Duration::from_secs(6);
+
+/// Fades work on the volume filter rather than the player volume, so `/volume` is left
+/// alone and a fade that gets cut short never changes it.
+#[derive(Debug, Default)]
+pub struct FadeState {
+    duration: Duration,
+    crossfade: bool,
+    /// Bumped by every fade, so an older fade still running knows to stop.
+    generation: u64,
+    /// Track whose ending fade has been planned already.
+    crossfaded_track: Option<String>,
+}
+
+/// Picks up the guild's fade settings. Called on every `track_start` and when they change.
+pub fn configure(data: &PlayerData, config: &GuildConfig) {
+    let mut state = data.state();
+    state.fade.duration = Duration::from_secs(config.fade_duration.max(0) as u64);
+    state.fade.crossfade = config.crossfade;
+}
+
+pub fn duration(data: &PlayerData) -> Duration {
+    data.state().fade.duration
+}
+
+async fn level(player: &PlayerContext) -> LavalinkResult<f64> {
+    Ok(player
+        .get_player()
+        .await?
+        .filters
+        .and_then(|f| f.volume)
+        .unwrap_or(1.0))
+}
+
+async fn set_level(player: &PlayerContext, level: f64) -> LavalinkResult<()> {
+    let mut filters = player.get_player().await?.filters.unwrap_or_default();
+    filters.volume = (level < 1.0).then_some(level.max(0.0));
+    player.set_filters(filters).await?;
+    Ok(())
+}
+
+/// Steps the volume filter from where it is to `to`. Returns early if another fade
+/// starts in the meantime.
+async fn ramp(
+    player: &PlayerContext,
+    data: &PlayerData,
+    to: f64,
+    over: Duration,
+) -> LavalinkResult<()> {
+    let generation = {
+        let mut state = data.state();
+        state.fade.generation += 1;
+        state.fade.generation
+    };
+
+    let from = level(player).await?;
+    let steps = (over.as_millis() / STEP.as_millis()).max(1) as u32;
+
+    for step in 1..=steps {
+        tokio::time::sleep(STEP).await;
+
+        if data.state().fade.generation != generation {
+            return Ok(());
+        }
+
+        let progress = f64::from(step) / f64::from(steps);
+        set_level(player, from + (to - from) * progress).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn fade_out(
+    player: &PlayerContext,
+    data: &PlayerData,
+    over: Duration,
+) -> LavalinkResult<()> {
+    ramp(player, data, 0.0, over).await
+}
+
+/// Puts the volume filter back to normal and stops any fade in progress.
+pub async fn reset(player: &PlayerContext, data: &PlayerData) -> LavalinkResult<()> {
+    data.state().fade.generation += 1;
+
+    if level(player).await? < 1.0 {
+        set_level(player, 1.0).await?;
+    }
+    Ok(())
+}
+
+/// Fades the new track in, or undoes a fade left over from the previous one.
+pub async fn track_started(player: &PlayerContext, data: &PlayerData) -> LavalinkResult<()> {
+    let over = {
+        let mut state = data.state();
+        state.fade.crossfaded_track = None;
+        state.fade.duration
+    };
+
+    if over.is_zero() {
+        return reset(player, data).await;
+    }
+
+    set_level(player, 0.0).await?;
+
+    let player = player.clone();
+    let data = data.clone();
+    tokio::spawn(async move {
+        if let Err(e) = ramp(&player, &data, 1.0, over).await {
+            error!("Failed to fade in: {:?}", e);
+        }
+    });
+
+    Ok(())
+}
+
+/// Called on every position update. Near the end of a track, plans a fade out that
+/// finishes as the track does, so the next one can fade in.
+pub async fn position_updated(player: &PlayerContext, data: &PlayerData, position: u64) {
+    let (over, crossfade) = {
+        let state = data.state();
+        (state.fade.duration, state.fade.crossfade)
+    };
+    if !crossfade || over.is_zero() {
+        return;
+    }
+
+    let Ok(current) = player.get_player().await else {
+        return;
+    };
+    let Some(track) = current.track else {
+        return;
+    };
+    if current.paused || track.info.is_stream || !track.info.is_seekable {
+        return;
+    }
+
+    let remaining = Duration::from_millis(track.info.length.saturating_sub(position));
+    if remaining > over + UPDATE_INTERVAL {
+        return;
+    }
+
+    {
+        let mut state = data.state();
+        if state.fade.crossfaded_track.as_ref() == Some(&track.encoded) {
+            return;
+        }
+        state.fade.crossfaded_track = Some(track.encoded.clone());
+    }
+
+    let player = player.clone();
+    let data = data.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(remaining.saturating_sub(over)).await;
+
+        let still_playing = player
+            .get_player()
+            .await
+            .is_ok_and(|p| p.track.is_some_and(|t| t.encoded == track.encoded) && !p.paused);
+        if !still_playing {
+            return;
+        }
+
+        if let Err(e) = fade_out(&player, &data, remaining.min(over)).await {
+            error!("Failed to fade out: {:?}", e);
+        }
+    });
+}
@@ -2,10 +2,25 @@ pub mod announcements;
 pub mod autodisconnect;
 pub mod embeds;
 pub mod emojis;
+pub mod fade;
 pub mod filters;
+pub mod nodes;
 pub mod permissions;
+pub mod playback;
 pub mod player_data;
+pub mod player_message;
+pub mod recovery;
+pub mod saved_queue;
+pub mod schedule;
+pub mod session;
+pub mod sleep;
+pub mod stage;
+pub mod stay;
+pub mod time;
+pub mod url_policy;
+pub mod voice_status;
 pub mod voicechannel;
+pub mod volume;
 pub mod constants {
     pub const COLOR_SUCCESS: u32 = 0x2ECC71;
     pub const COLOR_ERROR: u32 = 0xE74C3C;
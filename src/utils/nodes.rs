@@ -0,0 +1,340 @@
+use lavalink_rs::{
+    model::{
+        BoxFuture, events,
+        http::{ResumingState, UpdatePlayer, UpdatePlayerTrack},
+    },
+    node::Node,
+    prelude::{
+        GuildId, LavalinkClient, LavalinkResult, NodeBuilder, NodeDistributionStrategy, UserId,
+    },
+};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    env,
+    sync::{Arc, Mutex, atomic::Ordering},
+    time::Duration,
+};
+
+use crate::utils::player_data::PlayerData;
+
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+const RESUME_TIMEOUT_SECS: u32 = 60;
+
+/// One Lavalink node as configured in `LAVA_NODES`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NodeConfig {
+    pub host: String,
+    pub password: String,
+    #[serde(default)]
+    pub ssl: bool,
+    /// Matched against the guild's Discord voice endpoint, e.g. `rotterdam` or `us-east`.
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Relative share of players this node should take.
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+const fn default_weight() -> u32 {
+    1
+}
+
+impl NodeConfig {
+    /// Reads the node list from `LAVA_NODES`, a JSON array, falling back to the single
+    /// node described by `LAVA_HOST`, `LAVA_PASSWORD`, `LAVA_SSL` and `SESSION_ID`.
+    pub fn load() -> Vec<Self> {
+        if let Ok(nodes) = env::var("LAVA_NODES") {
+            let nodes: Vec<Self> = serde_json::from_str(&nodes).expect("Invalid LAVA_NODES");
+            assert!(!nodes.is_empty(), "LAVA_NODES is empty");
+            return nodes;
+        }
+
+        vec![Self {
+            host: env::var("LAVA_HOST").expect("LAVA_HOST not set"),
+            password: env::var("LAVA_PASSWORD").expect("LAVA_PASSWORD not set"),
+            ssl: env::var("LAVA_SSL").is_ok_and(|ssl| ssl == "true"),
+            region: None,
+            weight: default_weight(),
+            session_id: env::var("SESSION_ID").ok(),
+        }]
+    }
+
+    pub fn builder(&self, user_id: UserId) -> NodeBuilder {
+        NodeBuilder {
+            hostname: self.host.clone(),
+            is_ssl: self.ssl,
+            events: events::Events::default(),
+            password: self.password.clone(),
+            user_id,
+            session_id: self.session_id.clone(),
+        }
+    }
+}
+
+/// Stored as the Lavalink client's user data so the distribution strategy, which only
+/// gets the client, can see the node settings.
+pub struct NodeRegistry {
+    /// Same order as `LavalinkClient::nodes`.
+    configs: Vec<NodeConfig>,
+    voice_endpoints: Mutex<HashMap<GuildId, String>>,
+}
+
+impl NodeRegistry {
+    pub fn new(configs: Vec<NodeConfig>) -> Self {
+        Self {
+            configs,
+            voice_endpoints: Mutex::default(),
+        }
+    }
+
+    fn endpoints(&self) -> std::sync::MutexGuard<'_, HashMap<GuildId, String>> {
+        self.voice_endpoints
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+pub fn strategy() -> NodeDistributionStrategy {
+    NodeDistributionStrategy::custom(select_node)
+}
+
+/// Remembers the Discord voice server a guild was given, so a node in the same region
+/// can be picked for it. Call before creating the player.
+pub fn set_voice_endpoint(client: &LavalinkClient, guild_id: GuildId, endpoint: &str) {
+    if let Ok(registry) = client.data::<NodeRegistry>() {
+        registry.endpoints().insert(guild_id, endpoint.to_string());
+    }
+}
+
+pub fn forget_guild(client: &LavalinkClient, guild_id: GuildId) {
+    if let Ok(registry) = client.data::<NodeRegistry>() {
+        registry.endpoints().remove(&guild_id);
+    }
+}
+
+fn is_healthy(node: &Node) -> bool {
+    node.is_running.load(Ordering::SeqCst)
+}
+
+/// Lower is better. Players and CPU load are weighed the way Lavalink's own penalty
+/// system does it, then divided by the configured weight.
+fn load_score(client: &LavalinkClient, node: &Node, weight: u32) -> f64 {
+    let players = client
+        .players
+        .iter()
+        .filter(|entry| entry.1.id == node.id)
+        .count() as f64;
+
+    let cpu_penalty = 1.05f64.powf(100.0 * node.cpu.load().system_load) * 10.0 - 10.0;
+
+    (players + cpu_penalty) / weight.max(1) as f64
+}
+
+fn select_node(client: &LavalinkClient, guild_id: GuildId) -> BoxFuture<'_, Arc<Node>> {
+    Box::pin(async move {
+        let Ok(registry) = client.data::<NodeRegistry>() else {
+            return client.nodes[0].clone();
+        };
+
+        let healthy: Vec<(&Arc<Node>, &NodeConfig)> = client
+            .nodes
+            .iter()
+            .zip(&registry.configs)
+            .filter(|(node, _)| is_healthy(node))
+            .collect();
+
+        if healthy.is_empty() {
+            warn!("No healthy Lavalink nodes, using the first one");
+            return client.nodes[0].clone();
+        }
+
+        let endpoint = registry.endpoints().get(&guild_id).cloned();
+        let in_region: Vec<_> = healthy
+            .iter()
+            .filter(|(_, config)| {
+                config.region.as_deref().is_some_and(|region| {
+                    endpoint
+                        .as_deref()
+                        .is_some_and(|endpoint| endpoint.contains(region))
+                })
+            })
+            .copied()
+            .collect();
+
+        let candidates = if in_region.is_empty() {
+            healthy
+        } else {
+            in_region
+        };
+
+        candidates
+            .into_iter()
+            .min_by(|(a, a_config), (b, b_config)| {
+                load_score(client, a, a_config.weight).total_cmp(&load_score(
+                    client,
+                    b,
+                    b_config.weight,
+                ))
+            })
+            .map(|(node, _)| node.clone())
+            .unwrap_or_else(|| client.nodes[0].clone())
+    })
+}
+
+/// Watches node connections and moves the players of any node that went down.
+pub fn start_health_monitor(client: LavalinkClient) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+
+            if !client.nodes.iter().any(|node| is_healthy(node)) {
+                continue;
+            }
+
+            for node in client.nodes.iter().filter(|node| !is_healthy(node)) {
+                fail_over(&client, node).await;
+            }
+        }
+    });
+}
+
+fn guilds_on(client: &LavalinkClient, node: &Node) -> Vec<GuildId> {
+    client
+        .players
+        .iter()
+        .filter(|entry| entry.1.id == node.id)
+        .map(|entry| *entry.key())
+        .collect()
+}
+
+async fn fail_over(client: &LavalinkClient, dead: &Node) {
+    let guilds = guilds_on(client, dead);
+    if guilds.is_empty() {
+        return;
+    }
+
+    warn!(
+        "Lavalink node {} is down, moving {} players",
+        dead.websocket_address,
+        guilds.len()
+    );
+
+    recreate_players(client, guilds).await;
+}
+
+async fn recreate_players(client: &LavalinkClient, guilds: Vec<GuildId>) {
+    for guild_id in guilds {
+        if let Err(e) = recreate_player(client, guild_id).await {
+            error!(
+                "Failed to recreate player for guild {}: {:?}",
+                guild_id.0, e
+            );
+        }
+    }
+}
+
+/// Called whenever a node sends `ready`. Resuming is turned on so a short disconnect
+/// doesn't stop playback. If the node lost our session anyway, its players are created
+/// again from what we still have locally.
+pub async fn handle_ready(
+    client: &LavalinkClient,
+    session_id: &str,
+    resumed: bool,
+) -> LavalinkResult<()> {
+    let Some(node) = client
+        .nodes
+        .iter()
+        .find(|node| node.session_id.load().as_str() == session_id)
+    else {
+        return Ok(());
+    };
+
+    node.http
+        .set_resuming_state(
+            session_id,
+            &ResumingState {
+                resuming: Some(true),
+                timeout: Some(RESUME_TIMEOUT_SECS),
+            },
+        )
+        .await?;
+
+    if resumed {
+        // Events missed while disconnected are replayed, but our cached player state is
+        // only refreshed by the next player update.
+        for player in node.http.get_players(session_id).await? {
+            if let Some(context) = client.get_player_context(player.guild_id) {
+                context.update_player_data(player)?;
+            }
+        }
+        return Ok(());
+    }
+
+    let guilds = guilds_on(client, node);
+    if !guilds.is_empty() {
+        warn!(
+            "Lavalink node {} lost its session, recreating {} players",
+            node.websocket_address,
+            guilds.len()
+        );
+        recreate_players(client, guilds).await;
+    }
+
+    Ok(())
+}
+
+/// Creates the guild's player again on the least loaded healthy node and resumes the
+/// current track where it was, with the same queue, volume, filters and player data.
+async fn recreate_player(client: &LavalinkClient, guild_id: GuildId) -> LavalinkResult<()> {
+    let Some(old) = client.get_player_context(guild_id) else {
+        return Ok(());
+    };
+
+    let data = old.data::<PlayerData>()?;
+    let snapshot = old.get_player().await?;
+    let queue = old.get_queue().get_queue().await?;
+    // The last position the old session reported, so nothing is skipped.
+    let position = snapshot.state.position;
+
+    client.players.remove(&guild_id);
+    let _ = old.close();
+
+    set_voice_endpoint(client, guild_id, &snapshot.voice.endpoint);
+    let player = client
+        .create_player_context_with_data(guild_id, snapshot.voice.clone(), data.clone())
+        .await?;
+    player.get_queue().replace(queue)?;
+
+    if let Some(track) = &snapshot.track {
+        data.state().resumed_track = Some(track.encoded.clone());
+    }
+
+    player
+        .update_player(
+            &UpdatePlayer {
+                track: snapshot.track.as_ref().map(|track| UpdatePlayerTrack {
+                    encoded: Some(track.encoded.clone()),
+                    user_data: track.user_data.clone(),
+                    ..Default::default()
+                }),
+                position: snapshot.track.as_ref().map(|_| position),
+                volume: Some(snapshot.volume),
+                paused: Some(snapshot.paused),
+                filters: snapshot.filters,
+                ..Default::default()
+            },
+            false,
+        )
+        .await?;
+
+    info!(
+        "Recreated player for guild {} on {}",
+        guild_id.0,
+        client.get_node_for_guild(guild_id).await.websocket_address
+    );
+
+    Ok(())
+}
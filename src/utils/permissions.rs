@@ -17,17 +17,23 @@ pub async fn check_dj_or_admin(ctx: Context<'_>) -> Result<bool, Error> {
     }
 
     let guild_id = ctx.guild_id().ok_or("Must be in guild")?;
-    let db = ctx.data().database.pool();
+    let member = ctx.author_member().await.ok_or("Member not found")?;
+
+    member_is_dj(ctx.data().database.pool(), guild_id, &member).await
+}
 
-    let guild_id_i64 = guild_id.get() as i64;
-    let config = crate::database::queries::get_guild_config(db, guild_id_i64).await?;
+/// True when the guild has no DJ role configured or the member has it.
+pub async fn member_is_dj(
+    db: &sqlx::SqlitePool,
+    guild_id: serenity::GuildId,
+    member: &serenity::Member,
+) -> Result<bool, Error> {
+    let config = crate::database::queries::get_guild_config(db, guild_id.get() as i64).await?;
 
     let Some(dj_role_id) = config.dj_role_id else {
         return Ok(true);
     };
 
-    let member = ctx.author_member().await.ok_or("Member not found")?;
-
     Ok(member
         .roles
         .contains(&serenity::RoleId::new(dj_role_id as u64)))
@@ -35,12 +41,20 @@ pub async fn check_dj_or_admin(ctx: Context<'_>) -> Result<bool, Error> {
 
 pub async fn check_in_voice(ctx: Context<'_>) -> Result<bool, Error> {
     let guild_id = ctx.guild_id().ok_or("Must be in guild")?;
-    let author_id = ctx.author().id;
-    let cache = ctx.cache();
 
+    Ok(user_in_bot_channel(ctx.cache(), guild_id, ctx.author().id))
+}
+
+/// True when the user shares the bot's voice channel, or is in any voice channel
+/// while the bot isn't connected.
+pub fn user_in_bot_channel(
+    cache: &serenity::Cache,
+    guild_id: serenity::GuildId,
+    user_id: serenity::UserId,
+) -> bool {
     let user_voice = cache
         .guild(guild_id)
-        .and_then(|g| g.voice_states.get(&author_id).and_then(|vs| vs.channel_id));
+        .and_then(|g| g.voice_states.get(&user_id).and_then(|vs| vs.channel_id));
 
     let bot_voice = cache.guild(guild_id).and_then(|g| {
         g.voice_states
@@ -49,8 +63,8 @@ pub async fn check_in_voice(ctx: Context<'_>) -> Result<bool, Error> {
     });
 
     if bot_voice.is_none() {
-        return Ok(user_voice.is_some());
+        return user_voice.is_some();
     }
 
-    Ok(user_voice == bot_voice)
+    user_voice == bot_voice
 }
@@ -0,0 +1,227 @@
+use lavalink_rs::{
+    model::track::TrackData,
+    prelude::{LavalinkResult, PlayerContext, TrackInQueue},
+};
+use rand::seq::SliceRandom;
+
+use crate::utils::{
+    fade,
+    player_data::{LoopMode, PlayerData, UpNext},
+    stay,
+};
+
+const LOOP_MARKER: &str = "loop";
+
+/// lavalink-rs advances the queue by itself as soon as a track finishes, before our
+/// `track_end` hook runs, so looping works by keeping a copy of the playing track in
+/// the queue: at the front for track loop, at the back for queue loop.
+fn loop_copy(track: &TrackData) -> TrackInQueue {
+    let mut track = track.clone();
+
+    match &mut track.user_data {
+        Some(serde_json::Value::Object(data)) => {
+            data.remove("start_time");
+            data.insert(LOOP_MARKER.to_string(), true.into());
+        }
+        _ => track.user_data = Some(serde_json::json!({ LOOP_MARKER: true })),
+    }
+
+    track.into()
+}
+
+pub fn is_loop_copy(track: &TrackData) -> bool {
+    track
+        .user_data
+        .as_ref()
+        .is_some_and(|d| d[LOOP_MARKER].as_bool() == Some(true))
+}
+
+fn is_loop_copy_of(queued: &TrackInQueue, track: &TrackData) -> bool {
+    queued.track.encoded == track.encoded && is_loop_copy(&queued.track)
+}
+
+/// Queues the copy of `track` that `mode` needs. Called whenever a track starts.
+pub fn queue_loop_copy(
+    player: &PlayerContext,
+    mode: LoopMode,
+    track: &TrackData,
+) -> LavalinkResult<()> {
+    match mode {
+        LoopMode::Off => Ok(()),
+        LoopMode::Track => player.get_queue().push_to_front(loop_copy(track)),
+        LoopMode::Queue => player.get_queue().push_to_back(loop_copy(track)),
+    }
+}
+
+/// Notes what is queued behind the current track. Called when a track starts and on
+/// every position update, as the queue can't be trusted once the track has ended.
+pub async fn refresh_up_next(player: &PlayerContext, data: &PlayerData) {
+    let Ok(queue) = player.get_queue().get_queue().await else {
+        return;
+    };
+
+    data.state().up_next = UpNext {
+        requested: queue.iter().any(|t| !stay::is_fallback(&t.track)),
+        fallback: queue.iter().any(|t| stay::is_fallback(&t.track)),
+    };
+}
+
+async fn remove_loop_copy(
+    player: &PlayerContext,
+    mode: LoopMode,
+    track: &TrackData,
+) -> LavalinkResult<()> {
+    let queue = player.get_queue().get_queue().await?;
+
+    let index = match mode {
+        LoopMode::Off => None,
+        LoopMode::Track => queue.iter().position(|t| is_loop_copy_of(t, track)),
+        LoopMode::Queue => queue.iter().rposition(|t| is_loop_copy_of(t, track)),
+    };
+
+    if let Some(index) = index {
+        player.get_queue().remove(index)?;
+    }
+
+    Ok(())
+}
+
+pub async fn set_loop_mode(
+    player: &PlayerContext,
+    data: &PlayerData,
+    mode: LoopMode,
+) -> LavalinkResult<()> {
+    let previous = std::mem::replace(&mut data.state().loop_mode, mode);
+
+    if previous == mode {
+        return Ok(());
+    }
+
+    if let Some(track) = player.get_player().await?.track {
+        remove_loop_copy(player, previous, &track).await?;
+        queue_loop_copy(player, mode, &track)?;
+    }
+
+    Ok(())
+}
+
+/// Skips to the next track. With track loop on, the loop moves on to the next track
+/// instead of restarting the current one.
+pub async fn skip(player: &PlayerContext, data: &PlayerData) -> LavalinkResult<()> {
+    let current = player.get_player().await?.track;
+
+    let mode = {
+        let mut state = data.state();
+        if current.is_some() {
+            state.session.skipped += 1;
+        }
+        state.loop_mode
+    };
+
+    if mode == LoopMode::Track
+        && let Some(track) = &current
+    {
+        remove_loop_copy(player, mode, track).await?;
+    }
+
+    match current {
+        Some(track) => {
+            after_fade_out(player, data, track, |player| async move { player.skip() }).await
+        }
+        None => player.skip(),
+    }
+}
+
+/// Stops the current track without leaving its loop copy behind for the next `/play`.
+pub async fn stop(player: &PlayerContext, data: &PlayerData) -> LavalinkResult<()> {
+    let mode = data.state().loop_mode;
+
+    let Some(track) = player.get_player().await?.track else {
+        return Ok(());
+    };
+
+    remove_loop_copy(player, mode, &track).await?;
+
+    let data_after = data.clone();
+    after_fade_out(player, data, track, |player| async move {
+        player.stop_now().await?;
+        // Nothing will fade the next track in, so don't leave it silent.
+        fade::reset(&player, &data_after).await
+    })
+    .await
+}
+
+/// Like `stop`, but never waits for a fade.
+pub async fn stop_now(player: &PlayerContext, data: &PlayerData) -> LavalinkResult<()> {
+    let mode = data.state().loop_mode;
+
+    if let Some(track) = player.get_player().await?.track {
+        remove_loop_copy(player, mode, &track).await?;
+    }
+
+    player.stop_now().await?;
+    fade::reset(player, data).await
+}
+
+/// Runs `action` right away, or in the background once the track has faded out if the
+/// guild has fades on, so callers can answer without waiting for it.
+async fn after_fade_out<F, Fut>(
+    player: &PlayerContext,
+    data: &PlayerData,
+    track: TrackData,
+    action: F,
+) -> LavalinkResult<()>
+where
+    F: FnOnce(PlayerContext) -> Fut + Send + 'static,
+    Fut: Future<Output = LavalinkResult<()>> + Send,
+{
+    let over = fade::duration(data);
+    if over.is_zero() {
+        return action(player.clone()).await;
+    }
+
+    let player = player.clone();
+    let data = data.clone();
+    tokio::spawn(async move {
+        if let Err(e) = fade::fade_out(&player, &data, over).await {
+            error!("Failed to fade out: {:?}", e);
+        }
+
+        // The track may have ended by itself while fading.
+        let unchanged = player
+            .get_player()
+            .await
+            .is_ok_and(|p| p.track.is_some_and(|t| t.encoded == track.encoded));
+
+        if unchanged && let Err(e) = action(player).await {
+            error!("Failed to finish fading out: {:?}", e);
+        }
+    });
+
+    Ok(())
+}
+
+/// Shuffles the upcoming tracks, keeping a track loop copy in front.
+pub async fn shuffle(player: &PlayerContext, data: &PlayerData) -> LavalinkResult<usize> {
+    let mode = data.state().loop_mode;
+    let current = player.get_player().await?.track;
+
+    if mode == LoopMode::Track
+        && let Some(track) = &current
+    {
+        remove_loop_copy(player, mode, track).await?;
+    }
+
+    let mut queue = player.get_queue().get_queue().await?;
+    queue.make_contiguous().shuffle(&mut rand::rng());
+    let count = queue.len();
+    player.get_queue().replace(queue)?;
+
+    if mode == LoopMode::Track
+        && let Some(track) = &current
+    {
+        queue_loop_copy(player, mode, track)?;
+    }
+
+    Ok(count)
+}
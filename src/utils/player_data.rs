@@ -1,6 +1,84 @@
-use crate::websocket::server::ClientConnections;
+use crate::{
+    utils::{
+        autodisconnect::AutoDisconnect, fade::FadeState, session::SessionStats, sleep::SleepTimers,
+        voice_status::VoiceStatusState,
+    },
+    websocket::server::ClientConnections,
+};
 use poise::serenity_prelude as serenity;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LoopMode {
+    #[default]
+    Off,
+    Track,
+    Queue,
+}
+
+impl LoopMode {
+    pub const fn name(&self) -> &str {
+        match self {
+            Self::Off => "Off",
+            Self::Track => "Track",
+            Self::Queue => "Queue",
+        }
+    }
+
+    pub const fn next(&self) -> Self {
+        match self {
+            Self::Off => Self::Track,
+            Self::Track => Self::Queue,
+            Self::Queue => Self::Off,
+        }
+    }
+}
+
+/// What was queued behind the current track at the last check.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UpNext {
+    /// Tracks someone asked for, loop copies included.
+    pub requested: bool,
+    /// Tracks from the 24/7 fallback playlist.
+    pub fallback: bool,
+}
+
+impl UpNext {
+    pub const fn anything(&self) -> bool {
+        self.requested || self.fallback
+    }
+}
+
+/// Per-player state that changes while the player is alive.
+#[derive(Debug, Default)]
+pub struct PlayerState {
+    pub loop_mode: LoopMode,
+    pub filter: Option<String>,
+    pub player_message: Option<(serenity::ChannelId, serenity::MessageId)>,
+    pub announcements: Vec<(serenity::ChannelId, serenity::MessageId)>,
+    pub session: SessionStats,
+    pub session_thread: Option<serenity::ChannelId>,
+    pub updater_running: bool,
+    pub consecutive_failures: u32,
+    /// The current track has been heard playing, not just started.
+    pub playback_confirmed: bool,
+    /// Encoded track being replayed on a new node after failover, whose start should not
+    /// count as a new track.
+    pub resumed_track: Option<String>,
+    /// Playback was paused by us because everyone left.
+    pub paused_when_alone: bool,
+    /// Where the 24/7 fallback playlist continues from.
+    pub fallback_index: usize,
+    pub voice_channel: Option<serenity::ChannelId>,
+    /// The voice channel is a stage, whose topic follows the current track.
+    pub on_stage: bool,
+    pub voice_status: VoiceStatusState,
+    pub fade: FadeState,
+    /// lavalink-rs takes the next track before `track_end` runs, so that looks here.
+    pub up_next: UpNext,
+    /// An end-of-track sleep timer is waiting for the next track to start, to stop it.
+    pub sleep_on_next_start: bool,
+}
 
 #[derive(Clone)]
 pub struct PlayerData {
@@ -8,19 +86,27 @@ pub struct PlayerData {
     pub http: Arc<serenity::Http>,
     pub db: sqlx::SqlitePool,
     pub ws_clients: Option<ClientConnections>,
+    pub auto_disconnect: AutoDisconnect,
+    pub sleep_timers: SleepTimers,
+    pub state: Arc<Mutex<PlayerState>>,
 }
 
 impl PlayerData {
-    pub const fn new(
+    pub fn new(
         channel_id: serenity::ChannelId,
         http: Arc<serenity::Http>,
         db: sqlx::SqlitePool,
+        auto_disconnect: AutoDisconnect,
+        sleep_timers: SleepTimers,
     ) -> Self {
         Self {
             channel_id,
             http,
             db,
             ws_clients: None,
+            auto_disconnect,
+            sleep_timers,
+            state: Arc::default(),
         }
     }
 
@@ -29,12 +115,21 @@ impl PlayerData {
         http: Arc<serenity::Http>,
         db: sqlx::SqlitePool,
         ws_clients: ClientConnections,
+        auto_disconnect: AutoDisconnect,
+        sleep_timers: SleepTimers,
     ) -> Self {
         Self {
             channel_id,
             http,
             db,
             ws_clients: Some(ws_clients),
+            auto_disconnect,
+            sleep_timers,
+            state: Arc::default(),
         }
     }
+
+    pub fn state(&self) -> std::sync::MutexGuard<'_, PlayerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
 }
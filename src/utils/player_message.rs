@@ -0,0 +1,466 @@
+use lavalink_rs::{
+    model::track::TrackData,
+    prelude::{GuildId, LavalinkClient, LavalinkResult, PlayerContext},
+};
+use poise::serenity_prelude as serenity;
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    Data, Error,
+    database::queries,
+    utils::{
+        announcements::{AnnouncementBuilder, display_track},
+        constants::{COLOR_ERROR, COLOR_SUCCESS},
+        permissions, playback,
+        player_data::{LoopMode, PlayerData},
+        time::estimated_position,
+    },
+};
+
+const UPDATE_INTERVAL: Duration = Duration::from_secs(15);
+const BAR_LENGTH: usize = 16;
+const BUTTON_PREFIX: &str = "player:";
+
+pub struct PlayerStatus {
+    pub position: u64,
+    pub paused: bool,
+    pub volume: u16,
+    pub loop_mode: LoopMode,
+    pub filter: Option<String>,
+    pub queue_len: usize,
+}
+
+impl PlayerStatus {
+    pub async fn fetch(
+        player: &PlayerContext,
+        data: &PlayerData,
+    ) -> LavalinkResult<(Option<TrackData>, Self)> {
+        let player_data = player.get_player().await?;
+        let queue_len = player.get_queue().get_count().await?;
+        let state = data.state();
+
+        let status = Self {
+            position: estimated_position(&player_data),
+            paused: player_data.paused,
+            volume: player_data.volume,
+            loop_mode: state.loop_mode,
+            filter: state.filter.clone(),
+            queue_len,
+        };
+
+        Ok((player_data.track.as_ref().map(display_track), status))
+    }
+}
+
+pub fn progress_bar(position: u64, length: u64) -> String {
+    let filled = if length == 0 {
+        0
+    } else {
+        ((position.min(length) as f64 / length as f64) * BAR_LENGTH as f64) as usize
+    };
+
+    format!("{}{}", "▰".repeat(filled), "▱".repeat(BAR_LENGTH - filled))
+}
+
+pub fn controls(status: &PlayerStatus) -> Vec<serenity::CreateActionRow> {
+    let button = |action: &str, emoji: char| {
+        serenity::CreateButton::new(format!("{BUTTON_PREFIX}{action}"))
+            .emoji(emoji)
+            .style(serenity::ButtonStyle::Secondary)
+    };
+
+    let pause = if status.paused {
+        button("pause", '▶').style(serenity::ButtonStyle::Success)
+    } else {
+        button("pause", '⏸')
+    };
+
+    let repeat = match status.loop_mode {
+        LoopMode::Off => button("loop", '🔁'),
+        LoopMode::Track => button("loop", '🔂').style(serenity::ButtonStyle::Primary),
+        LoopMode::Queue => button("loop", '🔁').style(serenity::ButtonStyle::Primary),
+    };
+
+    vec![
+        serenity::CreateActionRow::Buttons(vec![
+            pause,
+            button("skip", '⏭'),
+            button("stop", '⏹').style(serenity::ButtonStyle::Danger),
+        ]),
+        serenity::CreateActionRow::Buttons(vec![
+            repeat,
+            button("shuffle", '🔀'),
+            button("favorite", '⭐'),
+        ]),
+    ]
+}
+
+/// Skip and favorite buttons for one-off replies such as `/nowplaying`.
+pub fn shortcuts() -> Vec<serenity::CreateActionRow> {
+    vec![serenity::CreateActionRow::Buttons(vec![
+        serenity::CreateButton::new(format!("{BUTTON_PREFIX}skip"))
+            .emoji('⏭')
+            .label("Skip")
+            .style(serenity::ButtonStyle::Secondary),
+        serenity::CreateButton::new(format!("{BUTTON_PREFIX}favorite"))
+            .emoji('⭐')
+            .label("Favorite")
+            .style(serenity::ButtonStyle::Secondary),
+    ])]
+}
+
+pub fn requester_id(track: &TrackData) -> u64 {
+    track
+        .user_data
+        .as_ref()
+        .and_then(|d| d["requester_id"].as_u64())
+        .unwrap_or_default()
+}
+
+/// Posts the player message in `channel_id`. With `edit_existing`, the guild's current
+/// player message there is edited instead, unless it was deleted. Otherwise the buttons
+/// are taken off the previous one so only the newest message has controls.
+pub async fn show(
+    data: &PlayerData,
+    channel_id: serenity::ChannelId,
+    track: &TrackData,
+    status: &PlayerStatus,
+    edit_existing: bool,
+) -> Result<serenity::MessageId, Error> {
+    let embed = AnnouncementBuilder::now_playing(track, requester_id(track), status);
+    let components = controls(status);
+
+    let existing = data.state().player_message;
+    if let Some((message_channel, message_id)) = existing {
+        if edit_existing && message_channel == channel_id {
+            let edit = serenity::EditMessage::new()
+                .embed(embed.clone())
+                .components(components.clone());
+
+            match channel_id.edit_message(&data.http, message_id, edit).await {
+                Ok(_) => return Ok(message_id),
+                Err(e) => warn!("Failed to edit player message, posting a new one: {:?}", e),
+            }
+        } else {
+            let _ = message_channel
+                .edit_message(
+                    &data.http,
+                    message_id,
+                    serenity::EditMessage::new().components(vec![]),
+                )
+                .await;
+        }
+    }
+
+    let message = channel_id
+        .send_message(
+            &data.http,
+            serenity::CreateMessage::default()
+                .embed(embed)
+                .components(components),
+        )
+        .await?;
+
+    data.state().player_message = Some((channel_id, message.id));
+    Ok(message.id)
+}
+
+/// Keeps the progress bar moving until the player goes away, then strips the buttons.
+pub fn start_updater(client: LavalinkClient, guild_id: GuildId, data: Arc<PlayerData>) {
+    {
+        let mut state = data.state();
+        if state.updater_running {
+            return;
+        }
+        state.updater_running = true;
+    }
+
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(UPDATE_INTERVAL).await;
+
+            let player = client.get_player_context(guild_id).filter(|player| {
+                player
+                    .data::<PlayerData>()
+                    .is_ok_and(|current| Arc::ptr_eq(&current, &data))
+            });
+
+            let Some(player) = player else {
+                break;
+            };
+
+            let Some((channel_id, message_id)) = data.state().player_message else {
+                continue;
+            };
+
+            let Ok((Some(track), status)) = PlayerStatus::fetch(&player, &data).await else {
+                continue;
+            };
+
+            if status.paused {
+                continue;
+            }
+
+            let edit = serenity::EditMessage::new()
+                .embed(AnnouncementBuilder::now_playing(
+                    &track,
+                    requester_id(&track),
+                    &status,
+                ))
+                .components(controls(&status));
+
+            if let Err(e) = channel_id.edit_message(&data.http, message_id, edit).await {
+                warn!("Player message for guild {} is gone: {:?}", guild_id.0, e);
+                data.state().player_message = None;
+            }
+        }
+
+        let message = {
+            let mut state = data.state();
+            state.updater_running = false;
+            state.player_message.take()
+        };
+
+        if let Some((channel_id, message_id)) = message {
+            let _ = channel_id
+                .edit_message(
+                    &data.http,
+                    message_id,
+                    serenity::EditMessage::new().components(vec![]),
+                )
+                .await;
+        }
+    });
+}
+
+async fn respond_error(
+    ctx: &serenity::Context,
+    interaction: &serenity::ComponentInteraction,
+    title: &str,
+    description: &str,
+) -> Result<(), Error> {
+    let embed = serenity::CreateEmbed::default()
+        .title(format!("<:forbidden2:1459603724895780970> {title}"))
+        .description(description)
+        .color(COLOR_ERROR);
+
+    interaction
+        .create_response(
+            ctx,
+            serenity::CreateInteractionResponse::Message(
+                serenity::CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn respond_success(
+    ctx: &serenity::Context,
+    interaction: &serenity::ComponentInteraction,
+    description: String,
+) -> Result<(), Error> {
+    let embed = serenity::CreateEmbed::default()
+        .description(description)
+        .color(COLOR_SUCCESS);
+
+    interaction
+        .create_response(
+            ctx,
+            serenity::CreateInteractionResponse::Message(
+                serenity::CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Handles presses on the player message buttons.
+pub async fn handle_interaction(
+    ctx: &serenity::Context,
+    interaction: &serenity::ComponentInteraction,
+    data: &Data,
+) -> Result<(), Error> {
+    let Some(action) = interaction.data.custom_id.strip_prefix(BUTTON_PREFIX) else {
+        return Ok(());
+    };
+    let Some(guild_id) = interaction.guild_id else {
+        return Ok(());
+    };
+
+    let Some(player) = data.lavalink.get_player_context(guild_id) else {
+        return respond_error(
+            ctx,
+            interaction,
+            "Not Connected",
+            "The bot is not in a voice channel.",
+        )
+        .await;
+    };
+    let player_data = player.data::<PlayerData>()?;
+
+    let Some(track) = player.get_player().await?.track else {
+        return respond_error(
+            ctx,
+            interaction,
+            "Nothing Playing",
+            "No track is currently playing.",
+        )
+        .await;
+    };
+    let track = display_track(&track);
+
+    if action == "favorite" {
+        return favorite(ctx, interaction, data, &track).await;
+    }
+
+    let member = interaction.member.as_ref().ok_or("Member not found")?;
+    let is_admin = member
+        .permissions
+        .is_some_and(|p| p.administrator() || p.manage_guild());
+
+    if !is_admin && !permissions::member_is_dj(data.database.pool(), guild_id, member).await? {
+        return respond_error(
+            ctx,
+            interaction,
+            "Permission Denied",
+            "You need the DJ role or admin permissions to use the player controls.",
+        )
+        .await;
+    }
+
+    if !permissions::user_in_bot_channel(&ctx.cache, guild_id, interaction.user.id) {
+        return respond_error(
+            ctx,
+            interaction,
+            "Not in Voice Channel",
+            "You must be in the same voice channel as the bot.",
+        )
+        .await;
+    }
+
+    match action {
+        "pause" => {
+            let paused = player.get_player().await?.paused;
+            player.set_pause(!paused).await?;
+        }
+        "skip" => {
+            playback::skip(&player, &player_data).await?;
+            return respond_success(
+                ctx,
+                interaction,
+                format!("Skipped **{} - {}**", track.info.author, track.info.title),
+            )
+            .await;
+        }
+        "stop" => {
+            playback::stop(&player, &player_data).await?;
+            interaction
+                .create_response(
+                    ctx,
+                    serenity::CreateInteractionResponse::UpdateMessage(
+                        serenity::CreateInteractionResponseMessage::new().components(vec![]),
+                    ),
+                )
+                .await?;
+            return Ok(());
+        }
+        "loop" => {
+            let mode = player_data.state().loop_mode.next();
+            playback::set_loop_mode(&player, &player_data, mode).await?;
+        }
+        "shuffle" => {
+            let count = playback::shuffle(&player, &player_data).await?;
+            if count < 2 {
+                return respond_error(
+                    ctx,
+                    interaction,
+                    "Nothing to Shuffle",
+                    "There need to be at least two tracks in the queue.",
+                )
+                .await;
+            }
+        }
+        _ => return Ok(()),
+    }
+
+    let (Some(track), status) = PlayerStatus::fetch(&player, &player_data).await? else {
+        interaction
+            .create_response(ctx, serenity::CreateInteractionResponse::Acknowledge)
+            .await?;
+        return Ok(());
+    };
+
+    interaction
+        .create_response(
+            ctx,
+            serenity::CreateInteractionResponse::UpdateMessage(
+                serenity::CreateInteractionResponseMessage::new()
+                    .embed(AnnouncementBuilder::now_playing(
+                        &track,
+                        requester_id(&track),
+                        &status,
+                    ))
+                    .components(controls(&status)),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn favorite(
+    ctx: &serenity::Context,
+    interaction: &serenity::ComponentInteraction,
+    data: &Data,
+    track: &TrackData,
+) -> Result<(), Error> {
+    let Some(uri) = track.info.uri.as_deref() else {
+        return respond_error(
+            ctx,
+            interaction,
+            "Cannot Favorite",
+            "This track has no link to save.",
+        )
+        .await;
+    };
+
+    let db = data.database.pool();
+    let user_id = interaction.user.id.get() as i64;
+
+    if queries::check_favorite_exists(db, user_id, uri).await? {
+        return respond_error(
+            ctx,
+            interaction,
+            "Already a Favorite",
+            "This track is already in your favorites.",
+        )
+        .await;
+    }
+
+    queries::add_favorite(
+        db,
+        user_id,
+        &track.info.title,
+        &track.info.author,
+        uri,
+        track.info.artwork_url.as_deref(),
+    )
+    .await?;
+
+    respond_success(
+        ctx,
+        interaction,
+        format!(
+            "Added **{} - {}** to your favorites",
+            track.info.author, track.info.title
+        ),
+    )
+    .await
+}
@@ -0,0 +1,253 @@
+use lavalink_rs::{
+    model::{
+        http::{UpdatePlayer, UpdatePlayerTrack},
+        track::TrackData,
+    },
+    prelude::{LavalinkResult, PlayerContext, SearchEngines, TrackLoadData},
+};
+
+use crate::utils::{
+    playback,
+    player_data::{LoopMode, PlayerData},
+};
+
+/// After this many failures in a row playback stops, so a broken playlist on loop
+/// cannot keep failing forever.
+const MAX_CONSECUTIVE_FAILURES: u32 = 5;
+const RETRY_KEY: &str = "retry_attempt";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Recovery {
+    Retrying,
+    AlternateSource(String),
+    Skipped,
+    Stopped,
+}
+
+impl Recovery {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Retrying => "Retrying the track...".to_string(),
+            Self::AlternateSource(source) => format!("Playing it from {source} instead."),
+            Self::Skipped => "Skipping to the next track.".to_string(),
+            Self::Stopped => {
+                format!("Stopped playback after {MAX_CONSECUTIVE_FAILURES} failures in a row.")
+            }
+        }
+    }
+}
+
+/// Works through the retry policy for a track that failed or got stuck: load the same
+/// URI again, then search for it on another source, then give up and move on.
+///
+/// A failed track has already been followed by lavalink-rs advancing the queue, while a
+/// stuck one is still the current track, so the replacement is placed accordingly.
+pub async fn recover(
+    player: &PlayerContext,
+    data: &PlayerData,
+    failed: &TrackData,
+    stuck: bool,
+) -> LavalinkResult<Recovery> {
+    let failures = {
+        let mut state = data.state();
+        state.consecutive_failures += 1;
+        state.consecutive_failures
+    };
+
+    if failures >= MAX_CONSECUTIVE_FAILURES {
+        data.state().consecutive_failures = 0;
+        playback::set_loop_mode(player, data, LoopMode::Off).await?;
+        player.stop_now().await?;
+        return Ok(Recovery::Stopped);
+    }
+
+    let attempt = failed
+        .user_data
+        .as_ref()
+        .and_then(|d| d[RETRY_KEY].as_u64())
+        .unwrap_or_default();
+
+    for stage in attempt..2 {
+        let Some((replacement, recovery)) = resolve(player, failed, stage).await else {
+            continue;
+        };
+
+        place(
+            player,
+            failed,
+            &with_attempt(replacement, failed, stage + 1),
+            stuck,
+        )
+        .await?;
+        return Ok(recovery);
+    }
+
+    if stuck {
+        playback::skip(player, data).await?;
+    }
+
+    Ok(Recovery::Skipped)
+}
+
+/// Called from `player_update` once the position moves past the start. TrackStart can't
+/// be trusted for this, as a load failure comes after it.
+pub async fn playback_confirmed(player: &PlayerContext, data: &PlayerData) {
+    {
+        let mut state = data.state();
+        if std::mem::replace(&mut state.playback_confirmed, true) {
+            return;
+        }
+        state.consecutive_failures = 0;
+    }
+
+    if let Ok(Some(track)) = player.get_player().await.map(|p| p.track) {
+        forget_attempt(player, &track, true).await;
+    }
+}
+
+/// A finished track played fine, so its loop copy starts over from the first attempt.
+pub async fn track_finished(player: &PlayerContext, data: &PlayerData, track: &TrackData) {
+    data.state().consecutive_failures = 0;
+    forget_attempt(player, track, false).await;
+}
+
+/// Drops the attempt count from a track that recovered, and from its loop copy, so a
+/// later failure gets the full retry policy again.
+async fn forget_attempt(player: &PlayerContext, track: &TrackData, playing: bool) {
+    let Some(user_data) = without_attempt(track) else {
+        return;
+    };
+
+    if playing {
+        // Without an encoded track Lavalink only replaces the user data.
+        let update = UpdatePlayer {
+            track: Some(UpdatePlayerTrack {
+                user_data: Some(user_data),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        if let Err(e) = player.update_player(&update, true).await {
+            error!("Failed to clear the retry attempt: {:?}", e);
+        }
+    }
+
+    let queue = player.get_queue();
+    let Ok(mut tracks) = queue.get_queue().await else {
+        return;
+    };
+
+    let mut changed = false;
+    for queued in tracks.iter_mut() {
+        if queued.track.encoded == track.encoded
+            && let Some(user_data) = without_attempt(&queued.track)
+        {
+            queued.track.user_data = Some(user_data);
+            changed = true;
+        }
+    }
+
+    if changed && let Err(e) = queue.replace(tracks) {
+        error!(
+            "Failed to clear the retry attempt of the loop copy: {:?}",
+            e
+        );
+    }
+}
+
+/// The track's user data without the attempt count, or `None` if it has none.
+fn without_attempt(track: &TrackData) -> Option<serde_json::Value> {
+    let mut user_data = track.user_data.clone()?;
+    user_data.as_object_mut()?.remove(RETRY_KEY)?;
+    Some(user_data)
+}
+
+async fn resolve(
+    player: &PlayerContext,
+    failed: &TrackData,
+    stage: u64,
+) -> Option<(TrackData, Recovery)> {
+    let (query, recovery) = match stage {
+        0 => (failed.info.uri.clone()?, Recovery::Retrying),
+        _ => {
+            // Uploaded files only exist at their URL.
+            if failed.info.source_name == "http" {
+                return None;
+            }
+
+            let (engine, source) = if failed.info.source_name == "youtube" {
+                (SearchEngines::SoundCloud, "SoundCloud")
+            } else {
+                (SearchEngines::YouTube, "YouTube")
+            };
+
+            let terms = format!("{} {}", failed.info.author, failed.info.title);
+            (
+                engine.to_query(&terms).ok()?,
+                Recovery::AlternateSource(source.to_string()),
+            )
+        }
+    };
+
+    let loaded = player
+        .client
+        .load_tracks(player.guild_id, &query)
+        .await
+        .ok()?;
+
+    let track = match loaded.data {
+        Some(TrackLoadData::Track(track)) => track,
+        Some(TrackLoadData::Search(tracks)) => tracks.into_iter().next()?,
+        _ => return None,
+    };
+
+    Some((track, recovery))
+}
+
+fn with_attempt(mut track: TrackData, failed: &TrackData, attempt: u64) -> TrackData {
+    let mut user_data = failed
+        .user_data
+        .clone()
+        .unwrap_or_else(|| serde_json::json!({}));
+
+    if let Some(data) = user_data.as_object_mut() {
+        data.remove("start_time");
+        data.insert(RETRY_KEY.to_string(), attempt.into());
+
+        // The title override belongs to the original upload, not a search result.
+        if attempt > 1 {
+            data.remove("title");
+        }
+    }
+
+    track.user_data = Some(user_data);
+    track
+}
+
+async fn place(
+    player: &PlayerContext,
+    failed: &TrackData,
+    replacement: &TrackData,
+    stuck: bool,
+) -> LavalinkResult<()> {
+    if stuck {
+        player.play_now(replacement).await?;
+        return Ok(());
+    }
+
+    match player.get_player().await?.track {
+        // The queue hasn't moved on yet, so the replacement will be picked up next.
+        Some(current) if current.encoded == failed.encoded => {
+            player.get_queue().push_to_front(replacement.clone())?;
+        }
+        Some(current) => {
+            player.get_queue().push_to_front(current)?;
+            player.play_now(replacement).await?;
+        }
+        None => {
+            player.play_now(replacement).await?;
+        }
+    }
+
+    Ok(())
+}
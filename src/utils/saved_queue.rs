@@ -0,0 +1,75 @@
+use lavalink_rs::{model::track::TrackData, prelude::PlayerContext};
+use poise::serenity_prelude as serenity;
+
+use crate::{
+    Error,
+    database::queries,
+    utils::{playback, stay},
+};
+
+/// Stores the current track, at its current position, and the rest of the queue.
+/// Loop copies and 24/7 fallback tracks are left out. Returns how many tracks were saved.
+pub async fn save(
+    player: &PlayerContext,
+    db: &sqlx::SqlitePool,
+    guild_id: serenity::GuildId,
+) -> Result<usize, Error> {
+    let player_data = player.get_player().await?;
+    let mut tracks = Vec::new();
+
+    if let Some(mut track) = player_data.track {
+        if track.info.is_seekable && !track.info.is_stream {
+            let position = player_data.state.position;
+            match &mut track.user_data {
+                Some(serde_json::Value::Object(data)) => {
+                    data.insert("start_time".to_string(), position.into());
+                }
+                _ => track.user_data = Some(serde_json::json!({ "start_time": position })),
+            }
+        }
+        tracks.push(track);
+    }
+
+    tracks.extend(
+        player
+            .get_queue()
+            .get_queue()
+            .await?
+            .into_iter()
+            .map(|queued| queued.track)
+            .filter(|track| !playback::is_loop_copy(track) && !stay::is_fallback(track)),
+    );
+
+    if tracks.is_empty() {
+        return Ok(0);
+    }
+
+    queries::save_queue(db, guild_id.get() as i64, &serde_json::to_string(&tracks)?).await?;
+    Ok(tracks.len())
+}
+
+/// Puts the guild's saved queue back at the end of the queue and starts it if nothing
+/// is playing. Returns how many tracks were restored.
+pub async fn restore(
+    player: &PlayerContext,
+    db: &sqlx::SqlitePool,
+    guild_id: serenity::GuildId,
+) -> Result<usize, Error> {
+    let Some(saved) = queries::take_saved_queue(db, guild_id.get() as i64).await? else {
+        return Ok(0);
+    };
+
+    let tracks: Vec<TrackData> = serde_json::from_str(&saved.tracks)?;
+    let count = tracks.len();
+
+    stay::drop_fallback(player).await?;
+    player
+        .get_queue()
+        .append(tracks.into_iter().map(Into::into).collect())?;
+
+    if player.get_player().await?.track.is_none() {
+        player.skip()?;
+    }
+
+    Ok(count)
+}
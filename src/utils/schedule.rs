@@ -0,0 +1,265 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use lavalink_rs::prelude::{TrackInQueue, TrackLoadData};
+use poise::serenity_prelude as serenity;
+use std::{collections::VecDeque, sync::Arc, time::Duration};
+
+use crate::{
+    Data, Error,
+    database::{models::ScheduledPlayback, queries},
+    utils::{
+        constants::{COLOR_ERROR, COLOR_PLAYLIST},
+        emojis::get_emoji,
+        stay,
+        time::parse_timestamp,
+        voicechannel::{connect, move_to},
+    },
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+/// A start missed by more than this, because the bot was offline, is skipped.
+const MAX_LATENESS_SECS: i64 = 10 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Repeat {
+    Never,
+    Daily,
+    Weekly,
+}
+
+impl Repeat {
+    pub const fn as_str(&self) -> &str {
+        match self {
+            Self::Never => "none",
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    pub fn from_db(repeat: &str) -> Self {
+        match repeat {
+            "daily" => Self::Daily,
+            "weekly" => Self::Weekly,
+            _ => Self::Never,
+        }
+    }
+
+    const fn interval_secs(&self) -> Option<i64> {
+        match self {
+            Self::Never => None,
+            Self::Daily => Some(24 * 60 * 60),
+            Self::Weekly => Some(7 * 24 * 60 * 60),
+        }
+    }
+}
+
+/// Parses a UTC date and time such as `2026-02-14 20:00`, an RFC 3339 timestamp, or a
+/// duration from now such as `2h30m`. Returns a Unix timestamp.
+pub fn parse_start(input: &str, now: i64) -> Option<i64> {
+    let input = input.trim();
+
+    for format in ["%Y-%m-%d %H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(input, format) {
+            return Some(time.and_utc().timestamp());
+        }
+    }
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(input) {
+        return Some(time.timestamp());
+    }
+
+    let after = parse_timestamp(input).filter(|d| !d.is_zero())?;
+    now.checked_add(i64::try_from(after.as_secs()).ok()?)
+}
+
+pub fn describe(schedule: &ScheduledPlayback, playlist_name: &str) -> String {
+    let repeat = match Repeat::from_db(&schedule.repeat_every) {
+        Repeat::Never => String::new(),
+        repeat => format!(", repeats {}", repeat.as_str()),
+    };
+
+    format!(
+        "`#{}` **{}** in <#{}> <t:{}:F> (<t:{}:R>){}",
+        schedule.id,
+        playlist_name,
+        schedule.voice_channel_id,
+        schedule.starts_at,
+        schedule.starts_at,
+        repeat
+    )
+}
+
+/// Checks for schedules that are due and starts them.
+pub fn start(ctx: serenity::Context, data: Arc<Data>) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+
+            if let Err(e) = run_due(&ctx, &data).await {
+                error!("Failed to run scheduled playbacks: {:?}", e);
+            }
+        }
+    });
+}
+
+async fn run_due(ctx: &serenity::Context, data: &Data) -> Result<(), Error> {
+    let db = data.database.pool();
+    let now = Utc::now().timestamp();
+
+    for mut schedule in queries::get_due_schedules(db, now).await? {
+        let starts_at = schedule.starts_at;
+
+        // Move on before starting, so a schedule that fails to start isn't retried
+        // every poll.
+        match Repeat::from_db(&schedule.repeat_every).interval_secs() {
+            Some(interval) => {
+                schedule.starts_at += interval * ((now - starts_at) / interval + 1);
+                queries::update_schedule(db, &schedule).await?;
+            }
+            None => {
+                queries::delete_schedule(db, schedule.id, schedule.guild_id).await?;
+            }
+        }
+
+        if now - starts_at > MAX_LATENESS_SECS {
+            warn!(
+                "Skipping scheduled playback {} in guild {}, it was due at {}",
+                schedule.id, schedule.guild_id, starts_at
+            );
+            continue;
+        }
+
+        if let Err(e) = begin(ctx, data, &schedule).await {
+            error!(
+                "Failed to start scheduled playback {} in guild {}: {:?}",
+                schedule.id, schedule.guild_id, e
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Joins the schedule's channel, or moves there, and puts the playlist in front of
+/// whatever was queued.
+async fn begin(
+    ctx: &serenity::Context,
+    data: &Data,
+    schedule: &ScheduledPlayback,
+) -> Result<(), Error> {
+    let db = data.database.pool();
+    let guild_id = serenity::GuildId::new(schedule.guild_id as u64);
+    let voice_channel = serenity::ChannelId::new(schedule.voice_channel_id as u64);
+
+    let config = queries::get_guild_config(db, schedule.guild_id).await?;
+    let text_channel = config
+        .announce_channel_id
+        .map_or(voice_channel, |id| serenity::ChannelId::new(id as u64));
+
+    let playlist = queries::get_guild_playlists(db, schedule.guild_id)
+        .await?
+        .into_iter()
+        .find(|p| p.id == schedule.playlist_id);
+
+    let Some(playlist) = playlist else {
+        let error_emoji = get_emoji(ctx, "cross").await;
+        let embed = serenity::CreateEmbed::default()
+            .title(format!(
+                "{} Scheduled Playback Failed",
+                error_emoji.unwrap_or_default()
+            ))
+            .description(format!(
+                "The playlist for schedule `#{}` no longer exists.",
+                schedule.id
+            ))
+            .color(COLOR_ERROR);
+
+        text_channel
+            .send_message(&ctx.http, serenity::CreateMessage::default().embed(embed))
+            .await?;
+        return Ok(());
+    };
+
+    let current_channel = ctx.cache.guild(guild_id).and_then(|g| {
+        g.voice_states
+            .get(&ctx.cache.current_user().id)
+            .and_then(|vs| vs.channel_id)
+    });
+
+    match data.lavalink.get_player_context(guild_id) {
+        None => connect(ctx, data, guild_id, voice_channel, text_channel).await?,
+        Some(_) if current_channel != Some(voice_channel) => {
+            move_to(ctx, data, guild_id, voice_channel).await?
+        }
+        Some(_) => {}
+    }
+
+    let player = data
+        .lavalink
+        .get_player_context(guild_id)
+        .ok_or("Failed to join the voice channel")?;
+
+    let mut tracks = VecDeque::new();
+    for saved in queries::get_playlist_tracks(db, playlist.id).await? {
+        let loaded = player
+            .client
+            .load_tracks(player.guild_id, &saved.uri)
+            .await?;
+
+        let mut track = match loaded.data {
+            Some(TrackLoadData::Track(track)) => track,
+            Some(TrackLoadData::Search(results)) if !results.is_empty() => results[0].clone(),
+            Some(TrackLoadData::Playlist(loaded)) if !loaded.tracks.is_empty() => {
+                loaded.tracks[0].clone()
+            }
+            _ => continue,
+        };
+
+        track.user_data = Some(serde_json::json!({ "requester_id": schedule.created_by }));
+        tracks.push_back(TrackInQueue::from(track));
+    }
+
+    if tracks.is_empty() {
+        return Err(format!("Playlist {} has no playable tracks", playlist.id).into());
+    }
+
+    let count = tracks.len();
+    stay::drop_fallback(&player).await?;
+    let queue = player.get_queue().get_queue().await?;
+
+    // What was playing picks up after the playlist instead of being skipped. A track
+    // loop already queued its own copy up front.
+    if let Some(current) = player.get_player().await?.track
+        && !stay::is_fallback(&current)
+        && queue
+            .front()
+            .is_none_or(|t| t.track.encoded != current.encoded)
+    {
+        tracks.push_back(TrackInQueue::from(current));
+    }
+    tracks.extend(queue);
+    player.get_queue().replace(tracks)?;
+    player.skip()?;
+
+    info!(
+        "Started scheduled playback {} in guild {}",
+        schedule.id, guild_id
+    );
+
+    let playlist_emoji = get_emoji(ctx, "album").await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "{} Scheduled Playback",
+            playlist_emoji.unwrap_or_default()
+        ))
+        .description(format!(
+            "Now playing **{}** ({count} tracks) in <#{voice_channel}>",
+            playlist.name
+        ))
+        .color(COLOR_PLAYLIST);
+
+    text_channel
+        .send_message(&ctx.http, serenity::CreateMessage::default().embed(embed))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude as serenity;
+use std::{collections::HashMap, time::Instant};
+
+use crate::{
+    database::queries,
+    utils::{
+        announcements::{self, AnnounceMode, AnnouncementBuilder},
+        player_data::PlayerData,
+    },
+    websocket::server::broadcast_player_event,
+};
+
+pub const TOP_REQUESTERS: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct SessionStats {
+    pub started_at: DateTime<Utc>,
+    pub tracks_played: u32,
+    pub skipped: u32,
+    pub listened_ms: u64,
+    pub requesters: HashMap<u64, u32>,
+    current_track: Option<(Instant, u64)>,
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self {
+            started_at: Utc::now(),
+            tracks_played: 0,
+            skipped: 0,
+            listened_ms: 0,
+            requesters: HashMap::new(),
+            current_track: None,
+        }
+    }
+}
+
+impl SessionStats {
+    pub fn duration_ms(&self) -> u64 {
+        (Utc::now() - self.started_at).num_milliseconds().max(0) as u64
+    }
+
+    /// `playable_ms` caps the time credited to this track, so a paused track
+    /// doesn't count for more than its own length.
+    pub fn track_started(&mut self, requester_id: u64, playable_ms: u64) {
+        self.track_ended();
+        self.tracks_played += 1;
+
+        if requester_id != 0 {
+            *self.requesters.entry(requester_id).or_default() += 1;
+        }
+
+        self.current_track = Some((Instant::now(), playable_ms));
+    }
+
+    pub fn track_ended(&mut self) {
+        if let Some((started, playable_ms)) = self.current_track.take() {
+            self.listened_ms += (started.elapsed().as_millis() as u64).min(playable_ms);
+        }
+    }
+
+    pub fn top_requesters(&self, count: usize) -> Vec<(u64, u32)> {
+        let mut requesters: Vec<_> = self.requesters.iter().map(|(id, n)| (*id, *n)).collect();
+        requesters.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        requesters.truncate(count);
+        requesters
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "started_at": self.started_at.to_rfc3339(),
+            "duration": self.duration_ms(),
+            "tracks_played": self.tracks_played,
+            "skipped": self.skipped,
+            "listening_time": self.listened_ms,
+            "top_requesters": self
+                .top_requesters(TOP_REQUESTERS)
+                .into_iter()
+                .map(|(id, tracks)| serde_json::json!({
+                    "user_id": id.to_string(),
+                    "tracks": tracks
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+pub async fn open_thread(
+    http: &serenity::Http,
+    channel_id: serenity::ChannelId,
+) -> Option<serenity::ChannelId> {
+    let name = format!("Music session {}", Utc::now().format("%Y-%m-%d %H:%M"));
+    let builder = serenity::CreateThread::new(name)
+        .kind(serenity::ChannelType::PublicThread)
+        .auto_archive_duration(serenity::AutoArchiveDuration::OneDay);
+
+    match channel_id.create_thread(http, builder).await {
+        Ok(thread) => Some(thread.id),
+        Err(e) => {
+            warn!("Failed to create session thread in {}: {:?}", channel_id, e);
+            None
+        }
+    }
+}
+
+/// Posts the session summary, to the session thread (which is then archived) or the
+/// announcement channel, and tells WebSocket subscribers the session is over.
+pub async fn close(data: &PlayerData, guild_id: serenity::GuildId) {
+    let (thread, stats) = {
+        let mut state = data.state();
+        state.session.track_ended();
+        (state.session_thread.take(), state.session.clone())
+    };
+
+    if let Some(ws_clients) = &data.ws_clients {
+        let _ = broadcast_player_event(ws_clients, guild_id.get(), "sessionEnded", stats.to_json())
+            .await;
+    }
+
+    let embed = AnnouncementBuilder::session_summary(&stats);
+
+    let Some(thread) = thread else {
+        if stats.tracks_played == 0 {
+            return;
+        }
+
+        let config = match queries::get_guild_config(&data.db, guild_id.get() as i64).await {
+            Ok(config) => config,
+            Err(e) => {
+                error!("Failed to get guild config for session summary: {:?}", e);
+                return;
+            }
+        };
+
+        if AnnounceMode::from_config(&config) == AnnounceMode::Off {
+            return;
+        }
+
+        let channel_id = announcements::announce_channel(data, &config);
+        if let Err(e) = channel_id
+            .send_message(&data.http, serenity::CreateMessage::default().embed(embed))
+            .await
+        {
+            warn!("Failed to post session summary: {:?}", e);
+        }
+        return;
+    };
+
+    if let Err(e) = thread
+        .send_message(&data.http, serenity::CreateMessage::default().embed(embed))
+        .await
+    {
+        warn!("Failed to post session summary: {:?}", e);
+    }
+
+    if let Err(e) = thread
+        .edit_thread(&data.http, serenity::EditThread::new().archived(true))
+        .await
+    {
+        warn!("Failed to archive session thread: {:?}", e);
+    }
+}
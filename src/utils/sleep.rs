@@ -0,0 +1,312 @@
+use lavalink_rs::{
+    model::{events::TrackEndReason, track::TrackData},
+    prelude::{LavalinkClient, PlayerContext},
+};
+use poise::serenity_prelude as serenity;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::task::AbortHandle;
+
+use crate::{
+    Error,
+    database::{models::SleepTimer, queries},
+    utils::{
+        constants::COLOR_INFO, emojis::get_emoji, fade, playback, player_data::PlayerData,
+        voicechannel::_leave,
+    },
+};
+
+const FADE_DURATION: Duration = Duration::from_secs(10);
+/// Longest `/sleep after` can wait.
+pub const MAX_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SleepMode {
+    After,
+    EndOfTrack,
+    EndOfQueue,
+}
+
+impl SleepMode {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::After => "after",
+            Self::EndOfTrack => "track",
+            Self::EndOfQueue => "queue",
+        }
+    }
+
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "after" => Some(Self::After),
+            "track" => Some(Self::EndOfTrack),
+            "queue" => Some(Self::EndOfQueue),
+            _ => None,
+        }
+    }
+}
+
+/// Describes a timer for `/sleep status` and friends.
+pub fn describe(timer: &SleepTimer) -> String {
+    let when = match (SleepMode::parse(&timer.mode), timer.ends_at) {
+        (Some(SleepMode::After), Some(ends_at)) => format!("<t:{ends_at}:R>"),
+        (Some(SleepMode::EndOfTrack), _) => "at the end of the current track".to_string(),
+        _ => "at the end of the queue".to_string(),
+    };
+
+    if timer.disconnect {
+        format!("Playback stops and I leave {when}")
+    } else {
+        format!("Playback stops {when}")
+    }
+}
+
+/// Handle to the sleep timers of every guild. Timers live in the database, but only the
+/// ones running on a clock survive a restart and need a task here.
+#[derive(Clone)]
+pub struct SleepTimers {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    ctx: serenity::Context,
+    lava_client: LavalinkClient,
+    db: sqlx::SqlitePool,
+    tasks: Mutex<HashMap<serenity::GuildId, AbortHandle>>,
+}
+
+impl SleepTimers {
+    pub fn start(
+        ctx: serenity::Context,
+        lava_client: LavalinkClient,
+        db: sqlx::SqlitePool,
+    ) -> Self {
+        let timers = Self {
+            inner: Arc::new(Inner {
+                ctx,
+                lava_client,
+                db,
+                tasks: Mutex::default(),
+            }),
+        };
+
+        let this = timers.clone();
+        tokio::spawn(async move {
+            match queries::get_sleep_timers(&this.inner.db).await {
+                Ok(saved) => {
+                    for timer in saved {
+                        let guild_id = serenity::GuildId::new(timer.guild_id as u64);
+
+                        // The session a track or queue timer was waiting on is gone after
+                        // a restart, and it would stop the next, unrelated one instead.
+                        if let Some(ends_at) = timer.ends_at {
+                            this.spawn_timer(guild_id, ends_at);
+                        } else if let Err(e) = this.cancel(guild_id).await {
+                            error!("Failed to delete stale sleep timer: {:?}", e);
+                        }
+                    }
+                }
+                Err(e) => error!("Failed to load sleep timers: {:?}", e),
+            }
+        });
+
+        timers
+    }
+
+    fn tasks(&self) -> std::sync::MutexGuard<'_, HashMap<serenity::GuildId, AbortHandle>> {
+        self.inner.tasks.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replaces the guild's timer. `after` is only used with `SleepMode::After`, and is
+    /// capped at `MAX_DURATION`.
+    pub async fn set(
+        &self,
+        guild_id: serenity::GuildId,
+        mode: SleepMode,
+        after: Duration,
+        disconnect: bool,
+        channel_id: serenity::ChannelId,
+    ) -> Result<SleepTimer, Error> {
+        let ends_at = (mode == SleepMode::After)
+            .then(|| chrono::Utc::now().timestamp() + after.min(MAX_DURATION).as_secs() as i64);
+
+        queries::set_sleep_timer(
+            &self.inner.db,
+            guild_id.get() as i64,
+            mode.as_str(),
+            ends_at,
+            disconnect,
+            channel_id.get() as i64,
+        )
+        .await?;
+
+        if let Some(task) = self.tasks().remove(&guild_id) {
+            task.abort();
+        }
+        if let Some(ends_at) = ends_at {
+            self.spawn_timer(guild_id, ends_at);
+        }
+
+        queries::get_sleep_timer(&self.inner.db, guild_id.get() as i64)
+            .await?
+            .ok_or_else(|| "Sleep timer was not saved".into())
+    }
+
+    /// Returns whether there was a timer to cancel.
+    pub async fn cancel(&self, guild_id: serenity::GuildId) -> Result<bool, Error> {
+        if let Some(task) = self.tasks().remove(&guild_id) {
+            task.abort();
+        }
+
+        Ok(queries::delete_sleep_timer(&self.inner.db, guild_id.get() as i64).await?)
+    }
+
+    fn spawn_timer(&self, guild_id: serenity::GuildId, ends_at: i64) {
+        let wait = (ends_at - chrono::Utc::now().timestamp()).max(0) as u64;
+        let this = self.clone();
+
+        let task = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+            this.went_off(guild_id, None).await;
+        });
+
+        if let Some(previous) = self.tasks().insert(guild_id, task.abort_handle()) {
+            previous.abort();
+        }
+    }
+
+    /// Called from `track_end` for the timers that wait for the music rather than the clock.
+    pub async fn track_ended(
+        &self,
+        player: &PlayerContext,
+        data: &PlayerData,
+        reason: &TrackEndReason,
+    ) {
+        if *reason == TrackEndReason::Replaced {
+            return;
+        }
+
+        let guild_id = serenity::GuildId::new(player.guild_id.0);
+        let Ok(Some(timer)) = queries::get_sleep_timer(&self.inner.db, guild_id.get() as i64).await
+        else {
+            return;
+        };
+
+        // Only these move on to the next track by themselves.
+        let advances = matches!(
+            reason,
+            TrackEndReason::Finished | TrackEndReason::LoadFailed
+        );
+        let up_next = data.state().up_next;
+
+        let due = match SleepMode::parse(&timer.mode) {
+            Some(SleepMode::EndOfTrack) => true,
+            Some(SleepMode::EndOfQueue) => !advances || !up_next.requested,
+            _ => false,
+        };
+        if !due {
+            return;
+        }
+
+        if advances && up_next.anything() {
+            // The next track is already on its way, stop it once it starts.
+            data.state().sleep_on_next_start = true;
+        } else {
+            self.went_off(guild_id, None).await;
+        }
+    }
+
+    /// Called from `track_start`. Returns true if a sleep timer stopped the track.
+    pub async fn track_started(
+        &self,
+        player: &PlayerContext,
+        data: &PlayerData,
+        track: &TrackData,
+    ) -> bool {
+        if !std::mem::take(&mut data.state().sleep_on_next_start) {
+            return false;
+        }
+
+        self.went_off(serenity::GuildId::new(player.guild_id.0), Some(track))
+            .await
+    }
+
+    /// Stops playback for the guild's timer, if it's still set. `started` is a track that
+    /// began after the timer was due, which is put back in the queue for later. Returns
+    /// whether the timer was still set.
+    async fn went_off(&self, guild_id: serenity::GuildId, started: Option<&TrackData>) -> bool {
+        // From here on the timer can't be cancelled, so a fade is never cut short.
+        self.tasks().remove(&guild_id);
+
+        let timer = match queries::get_sleep_timer(&self.inner.db, guild_id.get() as i64).await {
+            Ok(Some(timer)) => timer,
+            Ok(None) => return false,
+            Err(e) => {
+                error!("Failed to get sleep timer: {:?}", e);
+                return false;
+            }
+        };
+        if let Err(e) = queries::delete_sleep_timer(&self.inner.db, guild_id.get() as i64).await {
+            error!("Failed to delete sleep timer: {:?}", e);
+        }
+
+        // Nothing to stop if the bot already left.
+        let Some(player) = self.inner.lava_client.get_player_context(guild_id) else {
+            return true;
+        };
+
+        info!("Sleep timer went off in guild {}", guild_id);
+
+        if let Err(e) = stop(&player, started).await {
+            error!("Failed to stop for sleep timer: {:?}", e);
+        }
+
+        let mut description = "Sleep timer ended, playback stopped.".to_string();
+
+        if timer.disconnect
+            && let Some(manager) = songbird::get(&self.inner.ctx).await
+        {
+            match _leave(&self.inner.lava_client, &manager, guild_id).await {
+                Ok(()) => description = "Sleep timer ended, good night!".to_string(),
+                Err(e) => error!("Failed to leave for sleep timer: {:?}", e),
+            }
+        }
+
+        let clock_emoji = get_emoji(&self.inner.ctx, "clock").await;
+        let embed = serenity::CreateEmbed::default()
+            .title(format!("{} Sleep Timer", clock_emoji.unwrap_or_default()))
+            .description(description)
+            .color(COLOR_INFO);
+
+        let _ = serenity::ChannelId::new(timer.channel_id as u64)
+            .send_message(
+                &self.inner.ctx.http,
+                serenity::CreateMessage::default().embed(embed),
+            )
+            .await;
+
+        true
+    }
+}
+
+async fn stop(player: &PlayerContext, started: Option<&TrackData>) -> Result<(), Error> {
+    let data = player.data::<PlayerData>()?;
+
+    if let Some(track) = started {
+        playback::stop_now(player, &data).await?;
+        player.get_queue().push_to_front(track.clone())?;
+        return Ok(());
+    }
+
+    if player.get_player().await?.track.is_none() {
+        return Ok(());
+    }
+
+    fade::fade_out(player, &data, FADE_DURATION).await?;
+    playback::stop_now(player, &data).await?;
+
+    Ok(())
+}
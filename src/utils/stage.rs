@@ -0,0 +1,105 @@
+use lavalink_rs::{model::track::TrackData, prelude::PlayerContext};
+use poise::serenity_prelude as serenity;
+
+use crate::utils::{announcements::track_line, player_data::PlayerData};
+
+/// Discord's limit for stage instance topics.
+const MAX_TOPIC_LENGTH: usize = 120;
+
+/// Called whenever the bot ends up in a voice channel. On a stage it steps up to speak,
+/// and the topic is set to whatever is playing.
+pub async fn joined(
+    ctx: &serenity::Context,
+    player: &PlayerContext,
+    guild_id: serenity::GuildId,
+    channel_id: serenity::ChannelId,
+) {
+    let Ok(data) = player.data::<PlayerData>() else {
+        return;
+    };
+
+    // A move we made ourselves is reported again by the voice state update.
+    if data.state().voice_channel.replace(channel_id) == Some(channel_id) {
+        return;
+    }
+
+    let on_stage = take_stage(ctx, guild_id, channel_id).await;
+    data.state().on_stage = on_stage;
+
+    if on_stage && let Ok(Some(track)) = player.get_player().await.map(|p| p.track) {
+        set_topic(&ctx.http, channel_id, &topic(&track)).await;
+    }
+}
+
+/// Becomes a speaker when we're allowed to, or raises our hand when we're not.
+/// Returns whether the channel is a stage at all.
+async fn take_stage(
+    ctx: &serenity::Context,
+    guild_id: serenity::GuildId,
+    channel_id: serenity::ChannelId,
+) -> bool {
+    let channel = match ctx
+        .cache
+        .guild(guild_id)
+        .and_then(|g| g.channels.get(&channel_id).cloned())
+    {
+        Some(channel) => channel,
+        None => match channel_id.to_channel(ctx).await.map(|c| c.guild()) {
+            Ok(Some(channel)) => channel,
+            _ => return false,
+        },
+    };
+
+    if channel.kind != serenity::ChannelType::Stage {
+        return false;
+    }
+
+    let can_speak = ctx.cache.guild(guild_id).is_some_and(|guild| {
+        guild
+            .members
+            .get(&ctx.cache.current_user().id)
+            .is_some_and(|me| guild.user_permissions_in(&channel, me).mute_members())
+    });
+
+    if can_speak {
+        match channel
+            .edit_own_voice_state(ctx, serenity::EditVoiceState::new().suppress(false))
+            .await
+        {
+            Ok(()) => return true,
+            Err(e) => warn!("Failed to become a speaker in {}: {:?}", channel_id, e),
+        }
+    }
+
+    if let Err(e) = channel
+        .edit_own_voice_state(ctx, serenity::EditVoiceState::new().request_to_speak(true))
+        .await
+    {
+        warn!("Failed to request to speak in {}: {:?}", channel_id, e);
+    }
+
+    true
+}
+
+pub fn topic(track: &TrackData) -> String {
+    track_line(track, MAX_TOPIC_LENGTH)
+}
+
+/// Starts the stage with `topic`, or changes the topic if it's already live.
+pub async fn set_topic(http: &serenity::Http, channel_id: serenity::ChannelId, topic: &str) {
+    let result = match channel_id.get_stage_instance(http).await {
+        Ok(instance) if instance.topic == topic => return,
+        Ok(_) => channel_id
+            .edit_stage_instance(http, serenity::EditStageInstance::new().topic(topic))
+            .await
+            .map(|_| ()),
+        Err(_) => channel_id
+            .create_stage_instance(http, serenity::CreateStageInstance::new(topic))
+            .await
+            .map(|_| ()),
+    };
+
+    if let Err(e) = result {
+        warn!("Failed to set stage topic in {}: {:?}", channel_id, e);
+    }
+}
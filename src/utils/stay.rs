@@ -0,0 +1,139 @@
+use lavalink_rs::{
+    model::track::TrackData,
+    prelude::{LavalinkResult, PlayerContext, TrackLoadData},
+};
+use poise::serenity_prelude as serenity;
+use std::time::Duration;
+
+use crate::{
+    Data, Error,
+    database::{models::GuildConfig, queries},
+    utils::{player_data::PlayerData, saved_queue, voicechannel::connect},
+};
+
+const FALLBACK_KEY: &str = "fallback";
+const REJOIN_DELAY: Duration = Duration::from_secs(5);
+
+pub fn is_fallback(track: &TrackData) -> bool {
+    track
+        .user_data
+        .as_ref()
+        .is_some_and(|d| d[FALLBACK_KEY].as_bool() == Some(true))
+}
+
+/// Joins the guild's 24/7 channel unless the bot is already connected there or elsewhere,
+/// and picks up the saved queue, or else the fallback playlist.
+pub async fn rejoin(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: serenity::GuildId,
+) -> Result<(), Error> {
+    if data.lavalink.get_player_context(guild_id).is_some() {
+        return Ok(());
+    }
+
+    let config = queries::get_guild_config(data.database.pool(), guild_id.get() as i64).await?;
+    let Some(channel_id) = config.stay_channel_id else {
+        return Ok(());
+    };
+
+    let voice_channel = serenity::ChannelId::new(channel_id as u64);
+    let text_channel = config
+        .announce_channel_id
+        .map_or(voice_channel, |id| serenity::ChannelId::new(id as u64));
+
+    connect(ctx, data, guild_id, voice_channel, text_channel).await?;
+    info!("24/7: Joined {} in guild {}", voice_channel, guild_id);
+
+    let Some(player) = data.lavalink.get_player_context(guild_id) else {
+        return Ok(());
+    };
+
+    if saved_queue::restore(&player, data.database.pool(), guild_id).await? == 0
+        && queue_fallback(&player, &*player.data::<PlayerData>()?, &config).await?
+    {
+        player.skip()?;
+    }
+
+    Ok(())
+}
+
+/// Rejoins after being disconnected by someone else, once they've had a moment.
+pub async fn come_back(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: serenity::GuildId,
+) -> Result<(), Error> {
+    tokio::time::sleep(REJOIN_DELAY).await;
+    rejoin(ctx, data, guild_id).await
+}
+
+/// Queues the next track of the guild's fallback playlist if the queue has run dry.
+/// Returns whether a track was queued.
+pub async fn queue_fallback(
+    player: &PlayerContext,
+    data: &PlayerData,
+    config: &GuildConfig,
+) -> Result<bool, Error> {
+    let Some(playlist_id) = config.fallback_playlist_id else {
+        return Ok(false);
+    };
+
+    if config.stay_channel_id.is_none() || player.get_queue().get_count().await? > 0 {
+        return Ok(false);
+    }
+
+    let tracks = queries::get_playlist_tracks(&data.db, playlist_id).await?;
+
+    // Each track gets one chance, in case some of them no longer load.
+    for _ in 0..tracks.len() {
+        let index = {
+            let mut state = data.state();
+            let index = state.fallback_index % tracks.len();
+            state.fallback_index = index + 1;
+            index
+        };
+
+        let loaded = player
+            .client
+            .load_tracks(player.guild_id, &tracks[index].uri)
+            .await?;
+
+        let mut track = match loaded.data {
+            Some(TrackLoadData::Track(track)) => track,
+            Some(TrackLoadData::Search(tracks)) if !tracks.is_empty() => tracks[0].clone(),
+            Some(TrackLoadData::Playlist(playlist)) if !playlist.tracks.is_empty() => {
+                playlist.tracks[0].clone()
+            }
+            _ => continue,
+        };
+
+        // Lavalink knows the bot by its user id, which is who "requested" these.
+        let bot_id = player.client.nodes[0].user_id.0;
+        track.user_data = Some(serde_json::json!({
+            "requester_id": bot_id,
+            FALLBACK_KEY: true,
+        }));
+
+        player.get_queue().push_to_back(track)?;
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+/// Takes queued fallback tracks out so requested tracks don't wait behind them.
+pub async fn drop_fallback(player: &PlayerContext) -> LavalinkResult<()> {
+    let queue = player.get_queue().get_queue().await?;
+
+    if queue.iter().any(|t| is_fallback(&t.track)) {
+        player.get_queue().replace(
+            queue
+                .into_iter()
+                .filter(|t| !is_fallback(&t.track))
+                .collect(),
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,241 @@
+use lavalink_rs::model::player::Player;
+use std::time::Duration;
+use url::Url;
+
+pub fn format_duration(ms: u64) -> String {
+    let seconds = ms / 1000;
+    let minutes = seconds / 60;
+    let hours = minutes / 60;
+
+    if hours > 0 {
+        format!("{hours}:{:02}:{:02}", minutes % 60, seconds % 60)
+    } else {
+        format!("{minutes}:{:02}", seconds % 60)
+    }
+}
+
+/// Parses `90`, `1:30`, `1:02:03`, `90s`, `1m30s` or `1h2m3s`.
+pub fn parse_timestamp(input: &str) -> Option<Duration> {
+    let input = input.trim().to_lowercase();
+
+    if input.is_empty() {
+        return None;
+    }
+
+    if input.contains(':') {
+        let parts = input
+            .split(':')
+            .map(|p| p.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        if parts.len() > 3 || parts.iter().skip(1).any(|p| *p >= 60) {
+            return None;
+        }
+
+        let seconds = parts.iter().try_fold(0u64, |acc, p| {
+            acc.checked_mul(60).and_then(|acc| acc.checked_add(*p))
+        })?;
+        return Some(Duration::from_secs(seconds));
+    }
+
+    if let Ok(seconds) = input.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let mut seconds = 0u64;
+    let mut number = String::new();
+
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let value = number.parse::<u64>().ok()?;
+        number.clear();
+
+        let value = match c {
+            'h' => value.checked_mul(3600)?,
+            'm' => value.checked_mul(60)?,
+            's' => value,
+            _ => return None,
+        };
+        seconds = seconds.checked_add(value)?;
+    }
+
+    if !number.is_empty() {
+        return None;
+    }
+
+    Some(Duration::from_secs(seconds))
+}
+
+/// Reads the start position from links such as `?t=90`, `&start=90` or `#t=1m30s`.
+pub fn url_start_time(link: &str) -> Option<Duration> {
+    let url = Url::parse(link).ok()?;
+
+    let from_query = url
+        .query_pairs()
+        .find(|(key, _)| key == "t" || key == "start")
+        .and_then(|(_, value)| parse_timestamp(&value));
+
+    let from_fragment = || {
+        url.fragment()?
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("t="))
+            .and_then(parse_timestamp)
+    };
+
+    from_query
+        .or_else(from_fragment)
+        .filter(|start| !start.is_zero())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeekTarget {
+    Absolute(Duration),
+    Forward(Duration),
+    Backward(Duration),
+    Percent(f64),
+}
+
+impl SeekTarget {
+    /// Parses `1:23`, `1h2m`, `+30`, `-15` or `50%`.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+
+        if let Some(percent) = input.strip_suffix('%') {
+            let percent = percent.trim().parse::<f64>().ok()?;
+            return (0.0..=100.0)
+                .contains(&percent)
+                .then_some(Self::Percent(percent));
+        }
+
+        if let Some(offset) = input.strip_prefix('+') {
+            return parse_timestamp(offset).map(Self::Forward);
+        }
+
+        if let Some(offset) = input.strip_prefix('-') {
+            return parse_timestamp(offset).map(Self::Backward);
+        }
+
+        parse_timestamp(input).map(Self::Absolute)
+    }
+
+    /// Resolves the target to a position in milliseconds, clamped to the track length.
+    pub fn resolve(&self, position: u64, length: u64) -> u64 {
+        let millis = |d: &Duration| u64::try_from(d.as_millis()).unwrap_or(u64::MAX);
+        let target = match self {
+            Self::Absolute(at) => millis(at),
+            Self::Forward(by) => position.saturating_add(millis(by)),
+            Self::Backward(by) => position.saturating_sub(millis(by)),
+            Self::Percent(percent) => (length as f64 * percent / 100.0) as u64,
+        };
+
+        target.min(length)
+    }
+}
+
+/// Lavalink only reports the position every few seconds, so extrapolate from the last update.
+pub fn estimated_position(player: &Player) -> u64 {
+    let length = player.track.as_ref().map_or(0, |t| t.info.length);
+
+    if player.paused || player.state.time == 0 {
+        return player.state.position.min(length);
+    }
+
+    let elapsed = (chrono::Utc::now().timestamp_millis() as u64).saturating_sub(player.state.time);
+
+    player.state.position.saturating_add(elapsed).min(length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn parses_timestamps() {
+        assert_eq!(parse_timestamp("90"), Some(secs(90)));
+        assert_eq!(parse_timestamp("1:30"), Some(secs(90)));
+        assert_eq!(parse_timestamp("1:02:03"), Some(secs(3723)));
+        assert_eq!(parse_timestamp("90s"), Some(secs(90)));
+        assert_eq!(parse_timestamp("1m30s"), Some(secs(90)));
+        assert_eq!(parse_timestamp(" 1H2M3S "), Some(secs(3723)));
+    }
+
+    #[test]
+    fn rejects_invalid_timestamps() {
+        assert_eq!(parse_timestamp(""), None);
+        assert_eq!(parse_timestamp("1:60"), None);
+        assert_eq!(parse_timestamp("1:2:3:4"), None);
+        assert_eq!(parse_timestamp("1:x"), None);
+        assert_eq!(parse_timestamp("5d"), None);
+        assert_eq!(parse_timestamp("1m30"), None);
+        assert_eq!(parse_timestamp("h"), None);
+    }
+
+    #[test]
+    fn rejects_timestamps_that_overflow() {
+        assert_eq!(parse_timestamp("99999999999999999h"), None);
+        assert_eq!(parse_timestamp("9999999999999999999m"), None);
+        assert_eq!(parse_timestamp("18446744073709551615s1s"), None);
+        assert_eq!(parse_timestamp("9999999999999999999:00:00"), None);
+    }
+
+    #[test]
+    fn reads_start_time_from_links() {
+        assert_eq!(url_start_time("https://youtu.be/abc?t=90"), Some(secs(90)));
+        assert_eq!(
+            url_start_time("https://www.youtube.com/watch?v=abc&start=1m30s"),
+            Some(secs(90))
+        );
+        assert_eq!(
+            url_start_time("https://example.com/track#t=1:30"),
+            Some(secs(90))
+        );
+        assert_eq!(url_start_time("https://youtu.be/abc?t=0"), None);
+        assert_eq!(url_start_time("https://youtu.be/abc"), None);
+        assert_eq!(url_start_time("not a link"), None);
+    }
+
+    #[test]
+    fn parses_seek_targets() {
+        assert_eq!(
+            SeekTarget::parse("1:23"),
+            Some(SeekTarget::Absolute(secs(83)))
+        );
+        assert_eq!(
+            SeekTarget::parse("+30"),
+            Some(SeekTarget::Forward(secs(30)))
+        );
+        assert_eq!(
+            SeekTarget::parse("-15s"),
+            Some(SeekTarget::Backward(secs(15)))
+        );
+        assert_eq!(SeekTarget::parse("50%"), Some(SeekTarget::Percent(50.0)));
+        assert_eq!(SeekTarget::parse("150%"), None);
+        assert_eq!(SeekTarget::parse("abc"), None);
+    }
+
+    #[test]
+    fn resolves_seek_targets_within_the_track() {
+        let length = 200_000;
+
+        assert_eq!(SeekTarget::Absolute(secs(83)).resolve(0, length), 83_000);
+        assert_eq!(SeekTarget::Absolute(secs(300)).resolve(0, length), length);
+        assert_eq!(
+            SeekTarget::Forward(secs(30)).resolve(10_000, length),
+            40_000
+        );
+        assert_eq!(
+            SeekTarget::Forward(secs(u64::MAX)).resolve(10_000, length),
+            length
+        );
+        assert_eq!(SeekTarget::Backward(secs(30)).resolve(10_000, length), 0);
+        assert_eq!(SeekTarget::Percent(50.0).resolve(0, length), 100_000);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::database::queries;
+use url::Url;
+
+pub const RULE_ALLOW: &str = "allow";
+pub const RULE_DENY: &str = "deny";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlVerdict {
+    Allowed,
+    Blocked(String),
+}
+
+/// Whether the input is a web link that Lavalink will load as a URL rather than search
+/// for. The scheme is matched however it's capitalized, `HTTPS://` included.
+pub fn is_link(input: &str) -> bool {
+    Url::parse(input).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// Checks a link against the guild's domain rules before it is handed to Lavalink.
+///
+/// Deny rules always win. Once a guild has at least one allow rule, only links
+/// matching an allow rule are accepted.
+pub async fn check_url(
+    pool: &sqlx::SqlitePool,
+    guild_id: i64,
+    link: &str,
+) -> Result<UrlVerdict, sqlx::Error> {
+    let Ok(url) = Url::parse(link) else {
+        return Ok(UrlVerdict::Blocked(
+            "That doesn't look like a valid link.".to_string(),
+        ));
+    };
+
+    let Some(host) = url.host_str().map(|h| h.to_lowercase()) else {
+        return Ok(UrlVerdict::Blocked("That link has no domain.".to_string()));
+    };
+
+    let config = queries::get_guild_config(pool, guild_id).await?;
+    if url.scheme() == "http" && !config.allow_http_urls {
+        return Ok(UrlVerdict::Blocked(
+            "Unencrypted `http://` links are disabled on this server.".to_string(),
+        ));
+    }
+
+    let rules = queries::get_url_rules(pool, guild_id).await?;
+
+    if let Some(rule) = rules
+        .iter()
+        .find(|r| r.rule == RULE_DENY && domain_matches(&host, &r.domain))
+    {
+        return Ok(UrlVerdict::Blocked(format!(
+            "Links from `{}` are blocked on this server.",
+            rule.domain
+        )));
+    }
+
+    let allowed: Vec<&str> = rules
+        .iter()
+        .filter(|r| r.rule == RULE_ALLOW)
+        .map(|r| r.domain.as_str())
+        .collect();
+
+    if !allowed.is_empty() && !allowed.iter().any(|d| domain_matches(&host, d)) {
+        return Ok(UrlVerdict::Blocked(format!(
+            "Only links from {} are allowed on this server.",
+            allowed
+                .iter()
+                .map(|d| format!("`{d}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+
+    Ok(UrlVerdict::Allowed)
+}
+
+/// Normalizes user input such as `https://www.YouTube.com/watch` or `*.youtube.com`
+/// into a bare domain. Returns `None` if nothing domain-like is left.
+pub fn normalize_domain(input: &str) -> Option<String> {
+    let input = input.trim().to_lowercase();
+
+    let host = if input.contains("://") {
+        Url::parse(&input).ok()?.host_str()?.to_string()
+    } else {
+        input
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    let host = host
+        .trim_start_matches("*.")
+        .trim_start_matches('.')
+        .trim_start_matches("www.")
+        .trim_end_matches('.');
+
+    if host.is_empty()
+        || !host.contains('.')
+        || !host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    {
+        return None;
+    }
+
+    Some(host.to_string())
+}
+
+/// A rule for `youtube.com` also covers `www.youtube.com` and `music.youtube.com`.
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_are_recognized_however_they_are_capitalized() {
+        assert!(is_link("https://youtube.com/watch?v=abc"));
+        assert!(is_link("HTTP://denied.host/track"));
+        assert!(is_link("Https://denied.host/track"));
+        assert!(is_link(" https://denied.host/track"));
+    }
+
+    #[test]
+    fn searches_are_not_links() {
+        assert!(!is_link("never gonna give you up"));
+        assert!(!is_link("http"));
+        assert!(!is_link("ytsearch:song"));
+        assert!(!is_link("ftp://example.com/song.mp3"));
+    }
+
+    #[test]
+    fn normalizes_domains() {
+        assert_eq!(
+            normalize_domain("https://www.YouTube.com/watch").as_deref(),
+            Some("youtube.com")
+        );
+        assert_eq!(
+            normalize_domain("*.example.com").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(normalize_domain("localhost"), None);
+    }
+
+    #[test]
+    fn rules_cover_subdomains() {
+        assert!(domain_matches("music.youtube.com", "youtube.com"));
+        assert!(domain_matches("youtube.com", "youtube.com"));
+        assert!(!domain_matches("notyoutube.com", "youtube.com"));
+    }
+}
@@ -0,0 +1,105 @@
+use lavalink_rs::model::track::TrackData;
+use poise::serenity_prelude as serenity;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::utils::{announcements::track_line, player_data::PlayerData};
+
+/// Voice status changes are rate limited by Discord, so skipping through the queue only
+/// sends the track that ends up playing.
+const MIN_INTERVAL: Duration = Duration::from_secs(10);
+const MAX_STATUS_LENGTH: usize = 500;
+
+#[derive(Debug, Default)]
+pub struct VoiceStatusState {
+    /// What Discord was last told, and for which channel.
+    sent: Option<(serenity::ChannelId, String)>,
+    wanted: String,
+    last_sent: Option<Instant>,
+    pending: bool,
+}
+
+pub fn status(track: &TrackData) -> String {
+    track_line(track, MAX_STATUS_LENGTH)
+}
+
+/// Sets the bot's voice channel status, waiting out the rate limit if needed. Only the
+/// latest status is sent when several come in while waiting.
+pub fn set(data: &PlayerData, status: String) {
+    let wait = {
+        let mut state = data.state();
+        let voice_status = &mut state.voice_status;
+        voice_status.wanted = status;
+
+        if voice_status.pending {
+            return;
+        }
+        voice_status.pending = true;
+
+        voice_status.last_sent.map_or(Duration::ZERO, |at| {
+            (at + MIN_INTERVAL).saturating_duration_since(Instant::now())
+        })
+    };
+
+    let data = data.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(wait).await;
+        flush(&data).await;
+    });
+}
+
+pub fn clear(data: &PlayerData) {
+    set(data, String::new());
+}
+
+/// Clears the status without waiting for the rate limit, for when the bot is about to
+/// leave the channel and couldn't edit it afterwards.
+pub async fn clear_now(data: &PlayerData) {
+    data.state().voice_status.wanted = String::new();
+    flush(data).await;
+}
+
+async fn flush(data: &PlayerData) {
+    let (target, previous) = {
+        let mut state = data.state();
+        // Stage channels have a topic instead.
+        let channel_id = state.voice_channel.filter(|_| !state.on_stage);
+        let voice_status = &mut state.voice_status;
+        voice_status.pending = false;
+
+        let target = channel_id.map(|id| (id, voice_status.wanted.clone()));
+        let unchanged = match (&target, &voice_status.sent) {
+            (Some((_, status)), None) => status.is_empty(),
+            (target, sent) => target == sent,
+        };
+        if unchanged {
+            return;
+        }
+
+        voice_status.last_sent = Some(Instant::now());
+        let previous = std::mem::replace(&mut voice_status.sent, target.clone());
+        (target, previous)
+    };
+
+    // After a move the old channel would keep showing the track.
+    if let Some((channel_id, status)) = previous
+        && !status.is_empty()
+        && target.as_ref().is_none_or(|(id, _)| *id != channel_id)
+    {
+        send(data, channel_id, "").await;
+    }
+
+    if let Some((channel_id, status)) = target {
+        send(data, channel_id, &status).await;
+    }
+}
+
+async fn send(data: &PlayerData, channel_id: serenity::ChannelId, status: &str) {
+    if let Err(e) = data
+        .http
+        .edit_voice_status(channel_id, &serde_json::json!({ "status": status }), None)
+        .await
+    {
+        warn!("Failed to set voice status in {}: {:?}", channel_id, e);
+    }
+}
@@ -1,9 +1,20 @@
 use crate::{
-    Context, Error,
+    Context, Data, Error,
     database::queries,
-    utils::{autodisconnect::AutoDisconnectManager, player_data::PlayerData},
+    utils::{
+        announcements::{self, AnnounceMode},
+        constants::COLOR_WARNING,
+        nodes,
+        player_data::PlayerData,
+        saved_queue, session, stage, stay, voice_status, volume,
+    },
+};
+use ::serenity::all::CacheHttp;
+use lavalink_rs::{
+    client::LavalinkClient,
+    model::{http::UpdatePlayer, player::ConnectionInfo},
+    prelude::PlayerContext,
 };
-use ::serenity::all::{CacheHttp, EditVoiceState};
 use poise::serenity_prelude as serenity;
 use std::{ops::Deref, sync::Arc};
 
@@ -13,8 +24,6 @@ pub async fn _join(
     channel_id: Option<serenity::ChannelId>,
 ) -> Result<bool, Error> {
     let lava_client = ctx.data().lavalink.clone();
-    let db = ctx.data().database.pool();
-    let manager = songbird::get(ctx.serenity_context()).await.unwrap().clone();
 
     if lava_client.get_player_context(guild_id).is_none() {
         let connect_to = match channel_id {
@@ -35,71 +44,258 @@ pub async fn _join(
             }
         };
 
-        let handler = manager.join_gateway(guild_id, connect_to).await;
-
-        match handler {
-            Ok((connection_info, _)) => {
-                let ws_clients = ctx.data().ws_clients.clone();
-
-                let player_data = if let Some(clients) = ws_clients {
-                    PlayerData::with_ws_clients(
-                        ctx.channel_id(),
-                        ctx.serenity_context().http.clone(),
-                        ctx.data().database.pool().clone(),
-                        clients,
-                    )
-                } else {
-                    PlayerData::new(
-                        ctx.channel_id(),
-                        ctx.serenity_context().http.clone(),
-                        ctx.data().database.pool().clone(),
-                    )
-                };
-
-                let player_ctx = lava_client
-                    .create_player_context_with_data::<PlayerData>(
-                        guild_id,
-                        connection_info,
-                        Arc::new(player_data),
-                    )
-                    .await?;
-
-                let config = queries::get_guild_config(db, guild_id.get() as i64).await?;
-                player_ctx.set_volume(config.volume as u16).await?;
-
-                let auto_disconnect = AutoDisconnectManager::new(
-                    guild_id,
-                    db.clone(),
-                    ctx.serenity_context().clone(),
-                );
-                auto_disconnect.start_monitoring(lava_client.clone()).await;
-
-                if connect_to
-                    .get_stage_instance(ctx.serenity_context().http.clone())
-                    .await
-                    .is_ok()
-                {
-                    let http = ctx.serenity_context().http.clone();
-                    let channels = guild_id.channels(http).await.unwrap();
-                    let channel = channels.get(&channel_id.unwrap()).unwrap();
-                    let _ = channel
-                        .edit_own_voice_state(
-                            ctx.serenity_context().http(),
-                            EditVoiceState::new().request_to_speak(true),
-                        )
-                        .await;
-                }
-                return Ok(true);
-            }
-            Err(why) => {
-                return Err(why.into());
-            }
-        }
+        connect(
+            ctx.serenity_context(),
+            ctx.data(),
+            guild_id,
+            connect_to,
+            ctx.channel_id(),
+        )
+        .await?;
+
+        return Ok(true);
     }
 
     Ok(false)
 }
 
+/// Joins `voice_channel` and sets up a fresh player that posts in `text_channel`.
+/// Unlike `_join` this needs no command, so it is also used to join on our own.
+pub async fn connect(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: serenity::GuildId,
+    voice_channel: serenity::ChannelId,
+    text_channel: serenity::ChannelId,
+) -> Result<(), Error> {
+    let lava_client = &data.lavalink;
+    let db = data.database.pool();
+    let manager = songbird::get(ctx).await.ok_or("Songbird not registered")?;
+
+    let (connection_info, _) = manager.join_gateway(guild_id, voice_channel).await?;
+
+    let player_data = if let Some(clients) = data.ws_clients.clone() {
+        PlayerData::with_ws_clients(
+            text_channel,
+            ctx.http.clone(),
+            db.clone(),
+            clients,
+            data.auto_disconnect.clone(),
+            data.sleep_timers.clone(),
+        )
+    } else {
+        PlayerData::new(
+            text_channel,
+            ctx.http.clone(),
+            db.clone(),
+            data.auto_disconnect.clone(),
+            data.sleep_timers.clone(),
+        )
+    };
+
+    let player_data = Arc::new(player_data);
+    nodes::set_voice_endpoint(lava_client, guild_id.into(), &connection_info.endpoint);
+    let player_ctx = lava_client
+        .create_player_context_with_data::<PlayerData>(
+            guild_id,
+            connection_info,
+            player_data.clone(),
+        )
+        .await?;
+
+    let config = queries::get_guild_config(db, guild_id.get() as i64).await?;
+    player_ctx
+        .set_volume((config.volume.max(0) as u16).min(volume::guild_limit(&config)))
+        .await?;
+
+    if config.announce_thread && AnnounceMode::from_config(&config) != AnnounceMode::Off {
+        let parent = announcements::announce_channel(&player_data, &config);
+        player_data.state().session_thread = session::open_thread(&player_data.http, parent).await;
+    }
+
+    stage::joined(ctx, &player_ctx, guild_id, voice_channel).await;
+    data.auto_disconnect.notify(guild_id);
+
+    Ok(())
+}
+
+/// Moves the bot to another channel while keeping its player, so the queue, position,
+/// volume and filters carry over.
+pub async fn move_to(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: serenity::GuildId,
+    voice_channel: serenity::ChannelId,
+) -> Result<(), Error> {
+    let player = data
+        .lavalink
+        .get_player_context(guild_id)
+        .ok_or("Not connected")?;
+    let manager = songbird::get(ctx).await.ok_or("Songbird not registered")?;
+
+    let (connection_info, _) = manager.join_gateway(guild_id, voice_channel).await?;
+
+    nodes::set_voice_endpoint(&data.lavalink, guild_id.into(), &connection_info.endpoint);
+    let mut connection_info: ConnectionInfo = connection_info.into();
+    connection_info.fix();
+
+    player
+        .update_player(
+            &UpdatePlayer {
+                voice: Some(connection_info),
+                ..Default::default()
+            },
+            true,
+        )
+        .await?;
+
+    stage::joined(ctx, &player, guild_id, voice_channel).await;
+    data.auto_disconnect.notify(guild_id);
+
+    Ok(())
+}
+
+/// Disconnects from voice, destroys the player and closes the listening session.
+pub async fn _leave(
+    lava_client: &LavalinkClient,
+    manager: &songbird::Songbird,
+    guild_id: serenity::GuildId,
+) -> Result<(), Error> {
+    let player_data = lava_client
+        .get_player_context(guild_id)
+        .and_then(|player| player.data::<PlayerData>().ok());
+
+    if let Some(player_data) = &player_data {
+        voice_status::clear_now(player_data).await;
+    }
+
+    lava_client.delete_player(guild_id).await?;
+    nodes::forget_guild(lava_client, guild_id.into());
+
+    if manager.get(guild_id).is_some() {
+        manager.remove(guild_id).await?;
+    }
+
+    if let Some(player_data) = player_data {
+        session::close(&player_data, guild_id).await;
+
+        // A timer waiting for the end of a track would otherwise stop the next session.
+        if let Err(e) = player_data.sleep_timers.cancel(guild_id).await {
+            error!("Failed to cancel sleep timer: {:?}", e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Brings the player in line with the bot's own voice state after someone else moved
+/// or disconnected it.
+pub async fn bot_voice_state_changed(
+    ctx: &serenity::Context,
+    data: &Data,
+    old: Option<&serenity::VoiceState>,
+    new: &serenity::VoiceState,
+) -> Result<(), Error> {
+    let Some(guild_id) = new.guild_id else {
+        return Ok(());
+    };
+
+    // Our own leaves delete the player first, so if it's gone there is nothing to do.
+    let Some(player) = data.lavalink.get_player_context(guild_id) else {
+        return Ok(());
+    };
+
+    match new.channel_id {
+        None => disconnected(ctx, data, guild_id, &player).await,
+        Some(channel_id) if old.and_then(|o| o.channel_id) != Some(channel_id) => {
+            moved(ctx, guild_id, channel_id, &player).await
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+async fn moved(
+    ctx: &serenity::Context,
+    guild_id: serenity::GuildId,
+    channel_id: serenity::ChannelId,
+    player: &PlayerContext,
+) -> Result<(), Error> {
+    info!("Moved to {} in guild {}", channel_id, guild_id);
+
+    stage::joined(ctx, player, guild_id, channel_id).await;
+
+    let Some(call) = songbird::get(ctx).await.and_then(|m| m.get(guild_id)) else {
+        return Ok(());
+    };
+    let Some(connection_info) = call.lock().await.current_connection().cloned() else {
+        return Ok(());
+    };
+
+    let mut connection_info: ConnectionInfo = connection_info.into();
+    connection_info.fix();
+
+    player
+        .update_player(
+            &UpdatePlayer {
+                voice: Some(connection_info),
+                ..Default::default()
+            },
+            true,
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn disconnected(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: serenity::GuildId,
+    player: &PlayerContext,
+) -> Result<(), Error> {
+    info!("Disconnected from voice in guild {}", guild_id);
+
+    let db = data.database.pool();
+    let config = queries::get_guild_config(db, guild_id.get() as i64).await?;
+    let player_data = player.data::<PlayerData>()?;
+
+    let saved = if config.save_queue_on_disconnect {
+        saved_queue::save(player, db, guild_id).await?
+    } else {
+        0
+    };
+
+    if let Some(manager) = songbird::get(ctx).await {
+        _leave(&data.lavalink, &manager, guild_id).await?;
+    }
+
+    if config.stay_channel_id.is_some() {
+        return stay::come_back(ctx, data, guild_id).await;
+    }
+
+    let mut description = "I was disconnected from the voice channel.".to_string();
+    if saved > 0 {
+        description.push_str(&format!(
+            "\nSaved {saved} tracks, use `/restore` to bring them back."
+        ));
+    }
+
+    let embed = serenity::CreateEmbed::default()
+        .title("Disconnected")
+        .description(description)
+        .color(COLOR_WARNING);
+
+    let _ = player_data
+        .channel_id
+        .send_message(
+            &player_data.http,
+            serenity::CreateMessage::default().embed(embed),
+        )
+        .await;
+
+    Ok(())
+}
+
 pub fn check_user_in_voice(ctx: &Context<'_>, guild_id: serenity::GuildId) -> Result<bool, Error> {
     let cache = ctx.cache().expect("Expected cache");
     let user_voice = cache
@@ -0,0 +1,68 @@
+use lavalink_rs::{
+    model::player::Filters,
+    prelude::{LavalinkResult, PlayerContext},
+};
+use poise::serenity_prelude as serenity;
+
+use crate::{
+    Error,
+    database::{models::GuildConfig, queries},
+};
+
+/// Highest volume for paths that don't act for a member, like joining or the dashboard.
+pub fn guild_limit(config: &GuildConfig) -> u16 {
+    config.max_volume.max(0) as u16
+}
+
+/// The guild's limit, lowered by the strictest limit among the member's roles.
+pub async fn member_limit(
+    db: &sqlx::SqlitePool,
+    config: &GuildConfig,
+    member: &serenity::Member,
+) -> Result<u16, Error> {
+    let limit = queries::get_role_volume_limits(db, config.guild_id)
+        .await?
+        .into_iter()
+        .filter(|l| {
+            member
+                .roles
+                .contains(&serenity::RoleId::new(l.role_id as u64))
+        })
+        .map(|l| l.max_volume)
+        .fold(config.max_volume, i16::min);
+
+    Ok(limit.max(0) as u16)
+}
+
+/// Equalizer boosts make a track louder at the same volume, so the limit comes down by
+/// the biggest one. Bassboost's +0.30 turns a 200% limit into 153%.
+pub fn with_filters(limit: u16, filters: Option<&Filters>) -> u16 {
+    let boost = filters
+        .and_then(|f| f.equalizer.as_ref())
+        .into_iter()
+        .flatten()
+        .map(|band| band.gain)
+        .fold(0.0, f64::max);
+
+    (f64::from(limit) / (1.0 + boost)) as u16
+}
+
+/// Highest volume the player can be set to under `limit` with its current filters.
+pub async fn player_limit(player: &PlayerContext, limit: u16) -> LavalinkResult<u16> {
+    let current = player.get_player().await?;
+    Ok(with_filters(limit, current.filters.as_ref()))
+}
+
+/// Turns the player down if it's over `limit`, counting its filters. Returns the new
+/// volume when it had to change.
+pub async fn enforce(player: &PlayerContext, limit: u16) -> LavalinkResult<Option<u16>> {
+    let current = player.get_player().await?;
+    let limit = with_filters(limit, current.filters.as_ref());
+
+    if current.volume <= limit {
+        return Ok(None);
+    }
+
+    player.set_volume(limit).await?;
+    Ok(Some(limit))
+}
@@ -5,12 +5,21 @@ use lavalink_rs::prelude::{SearchEngines, TrackInQueue, TrackLoadData};
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{Mutex, RwLock};
 use tokio_tungstenite::WebSocketStream;
 use tokio_tungstenite::tungstenite::Message;
 
 use crate::Data;
+use crate::database::queries;
+use crate::utils::{
+    playback,
+    player_data::PlayerData,
+    stay,
+    url_policy::{self, UrlVerdict},
+    volume as volume_limit,
+};
 
 pub type WsStream = WebSocketStream<TcpStream>;
 pub type WsSender = SplitSink<WsStream, Message>;
@@ -31,10 +40,16 @@ impl WebSocketServer {
 
 impl WebSocketServer {
     pub fn new(addr: SocketAddr, data: Arc<Data>) -> Self {
+        // Share the map handed to players so their broadcasts reach our subscribers.
+        let clients = data
+            .ws_clients
+            .clone()
+            .unwrap_or_else(|| Arc::new(Mutex::new(HashMap::new())));
+
         Self {
             addr,
             data,
-            clients: Arc::new(Mutex::new(HashMap::new())),
+            clients,
         }
     }
 
@@ -119,29 +134,27 @@ async fn handle_client_messages(
                             .unwrap_or("unknown");
                         info!("Processing message type: {}", msg_type);
 
-                        if let Some("subscribe") = payload.get("type").and_then(|v| v.as_str()) {
-                            if let Some(guild_id_str) =
+                        if let Some("subscribe") = payload.get("type").and_then(|v| v.as_str())
+                            && let Some(guild_id_str) =
                                 payload.get("guild_id").and_then(|v| v.as_str())
-                            {
-                                if let Ok(guild_id) = guild_id_str.parse::<u64>() {
-                                    subscribed_guild = Some(guild_id);
-                                    let mut client_map = clients.lock().await;
-                                    client_map
-                                        .entry(guild_id)
-                                        .or_insert_with(Vec::new)
-                                        .push(Arc::clone(&sender));
-                                    info!("Client subscribed to guild: {}", guild_id);
-                                    let _ = send_response(
-                                        &sender,
-                                        "subscribed",
-                                        Some(serde_json::json!({
-                                            "guild_id": guild_id_str
-                                        })),
-                                    )
-                                    .await;
-                                    continue;
-                                }
-                            }
+                            && let Ok(guild_id) = guild_id_str.parse::<u64>()
+                        {
+                            subscribed_guild = Some(guild_id);
+                            let mut client_map = clients.lock().await;
+                            client_map
+                                .entry(guild_id)
+                                .or_insert_with(Vec::new)
+                                .push(Arc::clone(&sender));
+                            info!("Client subscribed to guild: {}", guild_id);
+                            let _ = send_response(
+                                &sender,
+                                "subscribed",
+                                Some(serde_json::json!({
+                                    "guild_id": guild_id_str
+                                })),
+                            )
+                            .await;
+                            continue;
                         }
 
                         if let Err(e) = process_message(&payload, &sender, &data).await {
@@ -164,7 +177,7 @@ async fn handle_client_messages(
                 debug!("Received binary message from client: {} bytes", data.len());
             }
             Message::Ping(ping) => {
-                if let Ok(_) = sender.write().await.send(Message::Pong(ping)).await {};
+                let _ = sender.write().await.send(Message::Pong(ping)).await;
             }
             Message::Pong(_) => {
                 debug!("Received pong from client");
@@ -271,16 +284,24 @@ async fn handle_play_request(
         guild_id, track_id
     );
 
+    if url_policy::is_link(track_id)
+        && let UrlVerdict::Blocked(reason) =
+            url_policy::check_url(data.database.pool(), guild_id as i64, track_id).await?
+    {
+        send_error_response(sender, reason).await?;
+        return Ok(());
+    }
+
     if let Some(player) = data.lavalink.get_player_context(guild_id) {
-        let query = if track_id.starts_with("http") {
+        let query = if url_policy::is_link(track_id) {
             track_id
         } else {
-            &SearchEngines::Spotify.to_query(&track_id)?
+            &SearchEngines::Spotify.to_query(track_id)?
         };
 
-        let loaded_tracks = data.lavalink.load_tracks(guild_id, &query).await?;
+        let loaded_tracks = data.lavalink.load_tracks(guild_id, query).await?;
 
-        let mut tracks: Vec<TrackInQueue> = match loaded_tracks.data {
+        let tracks: Vec<TrackInQueue> = match loaded_tracks.data {
             Some(TrackLoadData::Track(x)) => vec![x.into()],
             Some(TrackLoadData::Search(x)) => vec![x[0].clone().into()],
             Some(TrackLoadData::Playlist(x)) => x.tracks.iter().map(|x| x.clone().into()).collect(),
@@ -288,6 +309,7 @@ async fn handle_play_request(
                 return Ok(());
             }
         };
+        stay::drop_fallback(&player).await?;
         let queue = player.get_queue();
         queue.append(tracks.into())?;
 
@@ -335,12 +357,20 @@ async fn handle_skip_request(
 
     info!("Skip request for guild: {}", guild_id);
 
-    if let Some(player) = data.lavalink.get_player_context(guild_id) {
-        if let Err(e) = player.skip() {
-            error!("Failed to skip track: {}", e);
-            send_error_response(sender, format!("Failed to skip: {}", e)).await?;
-            return Ok(());
-        }
+    let Some(player) = data.lavalink.get_player_context(guild_id) else {
+        send_error_response(sender, "Not connected to a voice channel".to_string()).await?;
+        return Ok(());
+    };
+
+    let result = match player.data::<PlayerData>() {
+        Ok(player_data) => playback::skip(&player, &player_data).await,
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        error!("Failed to skip track: {}", e);
+        send_error_response(sender, format!("Failed to skip: {}", e)).await?;
+        return Ok(());
     }
 
     let response = serde_json::json!({
@@ -368,12 +398,12 @@ async fn handle_pause_request(
 
     info!("Pause request for guild: {}", guild_id);
 
-    if let Some(player) = data.lavalink.get_player_context(guild_id) {
-        if let Err(e) = player.set_pause(true).await {
-            error!("Failed to pause: {}", e);
-            send_error_response(sender, format!("Failed to pause: {}", e)).await?;
-            return Ok(());
-        }
+    if let Some(player) = data.lavalink.get_player_context(guild_id)
+        && let Err(e) = player.set_pause(true).await
+    {
+        error!("Failed to pause: {}", e);
+        send_error_response(sender, format!("Failed to pause: {}", e)).await?;
+        return Ok(());
     }
 
     let response = serde_json::json!({
@@ -401,12 +431,12 @@ async fn handle_resume_request(
 
     info!("Resume request for guild: {}", guild_id);
 
-    if let Some(player) = data.lavalink.get_player_context(guild_id) {
-        if let Err(e) = player.set_pause(false).await {
-            error!("Failed to resume: {}", e);
-            send_error_response(sender, format!("Failed to resume: {}", e)).await?;
-            return Ok(());
-        }
+    if let Some(player) = data.lavalink.get_player_context(guild_id)
+        && let Err(e) = player.set_pause(false).await
+    {
+        error!("Failed to resume: {}", e);
+        send_error_response(sender, format!("Failed to resume: {}", e)).await?;
+        return Ok(());
     }
 
     let response = serde_json::json!({
@@ -434,12 +464,20 @@ async fn handle_stop_request(
 
     info!("Stop request for guild: {}", guild_id);
 
-    if let Some(player) = data.lavalink.get_player_context(guild_id) {
-        if let Err(e) = player.stop_now().await {
-            error!("Failed to stop: {}", e);
-            send_error_response(sender, format!("Failed to stop: {}", e)).await?;
-            return Ok(());
-        }
+    let Some(player) = data.lavalink.get_player_context(guild_id) else {
+        send_error_response(sender, "Not connected to a voice channel".to_string()).await?;
+        return Ok(());
+    };
+
+    let result = match player.data::<PlayerData>() {
+        Ok(player_data) => playback::stop(&player, &player_data).await,
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        error!("Failed to stop: {}", e);
+        send_error_response(sender, format!("Failed to stop: {}", e)).await?;
+        return Ok(());
     }
 
     let response = serde_json::json!({
@@ -470,17 +508,32 @@ async fn handle_volume_request(
         .parse()
         .map_err(|_| "Invalid guild_id format")?;
 
-    if volume < 0 || volume > 1000 {
+    if !(0..=1000).contains(&volume) {
         return Err("Volume must be between 0 and 1000".into());
     }
 
     info!("Volume request for guild: {}, volume: {}", guild_id, volume);
 
+    // The dashboard acts for no particular member, so only the guild's limit applies.
+    let config = queries::get_guild_config(data.database.pool(), guild_id as i64).await?;
+    let limit = volume_limit::guild_limit(&config);
+    let mut volume = volume.min(i64::from(limit));
+
     if let Some(player) = data.lavalink.get_player_context(guild_id) {
-        if let Err(e) = player.set_volume(volume as u16).await {
-            error!("Failed to set volume: {}", e);
-            send_error_response(sender, format!("Failed to set volume: {}", e)).await?;
-            return Ok(());
+        let set = async {
+            let limit = volume_limit::player_limit(&player, limit).await?;
+            let set = limit.min(volume as u16);
+            player.set_volume(set).await?;
+            Ok::<_, lavalink_rs::error::LavalinkError>(set)
+        };
+
+        match set.await {
+            Ok(set) => volume = i64::from(set),
+            Err(e) => {
+                error!("Failed to set volume: {}", e);
+                send_error_response(sender, format!("Failed to set volume: {}", e)).await?;
+                return Ok(());
+            }
         }
     }
 
@@ -574,8 +627,39 @@ async fn handle_seek_request(
         guild_id, position
     );
 
-    if let Some(_player) = data.lavalink.get_player_context(guild_id) {
-        info!("Seek position set to: {} ms", position);
+    let Some(player) = data.lavalink.get_player_context(guild_id) else {
+        send_error_response(sender, "No active player for this guild".to_string()).await?;
+        return Ok(());
+    };
+
+    let Some(track) = player.get_player().await?.track else {
+        send_error_response(sender, "Nothing is currently playing".to_string()).await?;
+        return Ok(());
+    };
+
+    if !track.info.is_seekable || track.info.is_stream {
+        send_error_response(
+            sender,
+            "The current track does not support seeking".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let position = position as u64;
+    if position > track.info.length {
+        send_error_response(
+            sender,
+            format!("Position must be between 0 and {} ms", track.info.length),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if let Err(e) = player.set_position(Duration::from_millis(position)).await {
+        error!("Failed to seek: {}", e);
+        send_error_response(sender, format!("Failed to seek: {}", e)).await?;
+        return Ok(());
     }
 
     let response = serde_json::json!({
@@ -585,6 +669,16 @@ async fn handle_seek_request(
     });
 
     send_response(sender, "seek_response", Some(response)).await?;
+
+    if let Some(ws_clients) = &data.ws_clients {
+        let event_data = serde_json::json!({
+            "position": position,
+            "length": track.info.length,
+            "track": track
+        });
+        let _ = send_player_event(ws_clients, guild_id, "seek", event_data, Some(sender)).await;
+    }
+
     Ok(())
 }
 
@@ -712,6 +806,17 @@ pub async fn broadcast_player_event(
     guild_id: u64,
     event_type: &str,
     event_data: serde_json::Value,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    send_player_event(clients, guild_id, event_type, event_data, None).await
+}
+
+/// Sends a `playerEvent` to the guild's subscribers, skipping `exclude` if given.
+async fn send_player_event(
+    clients: &ClientConnections,
+    guild_id: u64,
+    event_type: &str,
+    event_data: serde_json::Value,
+    exclude: Option<&Arc<RwLock<WsSender>>>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let client_map = clients.lock().await;
 
@@ -727,6 +832,10 @@ pub async fn broadcast_player_event(
         let message_text = event.to_string();
 
         for sender in senders {
+            if exclude.is_some_and(|excluded| Arc::ptr_eq(excluded, sender)) {
+                continue;
+            }
+
             if let Err(e) = sender
                 .write()
                 .await